@@ -0,0 +1,123 @@
+use map::{Map, Tile};
+use render::{Theme, write_path};
+use std::cmp;
+use std::io;
+use std::io::prelude::*;
+use std::sync::mpsc::{Receiver, RecvTimeoutError, channel};
+use std::thread;
+use std::time::Duration;
+
+const CLEAR: &'static str = "\x1b[2J\x1b[H";
+const MIN_DELAY: u64 = 10;
+const MAX_DELAY: u64 = 5000;
+
+// A map as it looks after a move, with the moves to draw over it.
+pub struct Frame {
+    pub map: Map,
+    pub moves: Vec<(char, i32, i32)>,
+}
+
+// The frames of a path where the snake never leaves a cell: every visited
+// cell becomes part of the body and eaten food disappears. Hazards are shown
+// as they are after each move.
+pub fn trail(map: &Map, path: &[(char, i32, i32)]) -> Vec<Frame> {
+    let mut curr = map.clone();
+    let mut frames = Vec::new();
+    let mut time = 0;
+    for &(m, y, x) in path {
+        if curr.get(y, x) == Some(Tile::Food) {
+            curr.food -= 1;
+        }
+        curr.set(curr.head.0, curr.head.1, Tile::Snake);
+        curr.set(y, x, Tile::Snake);
+        curr.head = (y, x);
+        time += map.cost(y, x);
+        frames.push(Frame { map: curr.at_time(time), moves: vec![(m, y, x)] });
+    }
+    frames
+}
+
+// Plays frames in the terminal. Commands are read from stdin, one per
+// character, once Enter is pressed.
+pub struct Animation {
+    delay: u64,
+    input: Receiver<char>,
+}
+
+fn print_help() {
+    println!("p: pause/resume, n: next move (pauses), +/-: faster/slower, q: stop, then Enter");
+}
+
+impl Animation {
+    pub fn new(delay: u64) -> Animation {
+        let (tx, rx) = channel();
+        thread::spawn(move || {
+            let stdin = io::stdin();
+            for line in stdin.lock().lines() {
+                let line = match line {
+                    Ok(line) => line,
+                    Err(_) => return,
+                };
+                for c in line.chars() {
+                    if tx.send(c).is_err() {
+                        return;
+                    }
+                }
+            }
+        });
+        Animation { delay: delay, input: rx }
+    }
+
+    // Show the frames one after the other, then how far the snake got.
+    pub fn play(&mut self, map: &Map, frames: &[Frame], theme: &Theme) {
+        let mut paused = false;
+        let mut shown = 0;
+        while shown < frames.len() {
+            let frame = &frames[shown];
+            print!("{}", CLEAR);
+            write_path(&mut io::stdout(), &frame.map, &frame.moves, theme).unwrap();
+            println!("Move {}/{}, {} ms per move{}", shown + 1, frames.len(), self.delay,
+                     if paused { ", paused" } else { "" });
+            print_help();
+            io::stdout().flush().unwrap();
+
+            let command = if paused {
+                self.input.recv().map_err(|_| RecvTimeoutError::Disconnected)
+            } else {
+                self.input.recv_timeout(Duration::from_millis(self.delay))
+            };
+            match command {
+                Ok('p') | Ok(' ') => paused = !paused,
+                Ok('n') => {
+                    paused = true;
+                    shown += 1;
+                },
+                Ok('+') => self.delay = cmp::max(self.delay / 2, MIN_DELAY),
+                Ok('-') => self.delay = cmp::min(self.delay * 2, MAX_DELAY),
+                Ok('q') => {
+                    shown += 1;
+                    break;
+                },
+                Ok(_) => {},
+                Err(RecvTimeoutError::Timeout) => shown += 1,
+                // Nothing more to read: just play on.
+                Err(RecvTimeoutError::Disconnected) => {
+                    if !paused {
+                        thread::sleep(Duration::from_millis(self.delay));
+                    }
+                    paused = false;
+                    shown += 1;
+                },
+            }
+        }
+        let shown = cmp::min(shown, frames.len());
+        let moves: Vec<(char, i32, i32)> = frames[..shown].iter().flat_map(|f| f.moves.clone()).collect();
+        let left = frames[..shown].last().map_or(map.food, |f| f.map.food);
+        if shown < frames.len() {
+            print!("Stopped after {} of {} moves", shown, frames.len());
+        } else {
+            print!("Done in {} moves", shown);
+        }
+        println!(" (cost {}), {} of {} food eaten", map.path_cost(&moves), map.food - left, map.food);
+    }
+}
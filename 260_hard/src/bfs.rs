@@ -0,0 +1,134 @@
+use map::{Map, Tile};
+use search::moves;
+use std::collections::BinaryHeap;
+
+// For every cell, the cells one move away with the move and its cost.
+fn edges(map: &Map) -> Vec<Vec<(char, usize, i32)>> {
+    let mut edges = Vec::new();
+    for y in 0..map.height {
+        for x in 0..map.width {
+            let mut out = Vec::new();
+            for &(m, dy, dx) in moves(map) {
+                if let Some(((ny, nx), _)) = map.step(y, x, dy, dx) {
+                    out.push((m, (ny * map.width + nx) as usize, map.cost(ny, nx)));
+                }
+            }
+            edges.push(out);
+        }
+    }
+    edges
+}
+
+fn reversed(edges: &[Vec<(char, usize, i32)>]) -> Vec<Vec<(char, usize, i32)>> {
+    let mut rev = vec![Vec::new(); edges.len()];
+    for (from, out) in edges.iter().enumerate() {
+        for &(m, to, cost) in out {
+            rev[to].push((m, from, cost));
+        }
+    }
+    rev
+}
+
+// Shortest paths from one cell (or to one cell, when reversed) ignoring the
+// snake itself, so going through anything the map allows.
+pub struct Bfs {
+    width: i32,
+    pub dist: Vec<Option<i32>>,
+    prev: Vec<Option<(char, usize)>>,
+}
+
+impl Bfs {
+    pub fn new(map: &Map, from: (i32, i32)) -> Bfs {
+        Bfs::search(map, &edges(map), from)
+    }
+
+    fn search(map: &Map, edges: &[Vec<(char, usize, i32)>], from: (i32, i32)) -> Bfs {
+        let size = (map.width * map.height) as usize;
+        let mut bfs = Bfs { width: map.width, dist: vec![None; size], prev: vec![None; size] };
+        // Max heap on the negated distance.
+        let mut queue = BinaryHeap::new();
+        let start = bfs.index(from);
+
+        bfs.dist[start] = Some(0);
+        queue.push((0, start));
+        while let Some((d, curr)) = queue.pop() {
+            if Some(-d) != bfs.dist[curr] {
+                continue;
+            }
+            for &(m, next, cost) in &edges[curr] {
+                let nd = -d + cost;
+                if bfs.dist[next].map_or(true, |old| nd < old) {
+                    bfs.dist[next] = Some(nd);
+                    bfs.prev[next] = Some((m, curr));
+                    queue.push((-nd, next));
+                }
+            }
+        }
+        bfs
+    }
+
+    fn index(&self, (y, x): (i32, i32)) -> usize {
+        (y * self.width + x) as usize
+    }
+
+    pub fn distance(&self, to: (i32, i32)) -> Option<i32> {
+        self.dist[self.index(to)]
+    }
+
+    pub fn path_to(&self, to: (i32, i32)) -> Vec<(char, i32, i32)> {
+        let mut path = Vec::new();
+        let mut curr = self.index(to);
+        while let Some((m, prev)) = self.prev[curr] {
+            path.push((m, curr as i32 / self.width, curr as i32 % self.width));
+            curr = prev;
+        }
+        path.reverse();
+        path
+    }
+}
+
+// Shortest distances from and to every food, used as lower bounds by the
+// heuristics. Portals and one-way tiles make them asymmetric.
+pub struct FoodDistances {
+    food: Vec<(i32, i32)>,
+    from: Vec<Bfs>,
+    to: Vec<Bfs>,
+}
+
+const FAR: i32 = ::std::i32::MAX / 1024;
+
+impl FoodDistances {
+    pub fn new(map: &Map, food: &[(i32, i32)]) -> FoodDistances {
+        let edges = edges(map);
+        let rev = reversed(&edges);
+        FoodDistances {
+            food: food.to_vec(),
+            from: food.iter().map(|&f| Bfs::search(map, &edges, f)).collect(),
+            to: food.iter().map(|&f| Bfs::search(map, &rev, f)).collect(),
+        }
+    }
+
+    // A lower bound on the distance between a cell and food f, whatever the
+    // direction.
+    pub fn cell(&self, cell: (i32, i32), f: usize) -> i32 {
+        let a = self.to[f].distance(cell).unwrap_or(FAR);
+        let b = self.from[f].distance(cell).unwrap_or(FAR);
+        ::std::cmp::min(a, b)
+    }
+
+    pub fn between(&self, f: usize, g: usize) -> i32 {
+        let a = self.from[f].distance(self.food[g]).unwrap_or(FAR);
+        let b = self.from[g].distance(self.food[f]).unwrap_or(FAR);
+        ::std::cmp::min(a, b)
+    }
+}
+
+// Food that no path from any head can ever reach.
+pub fn unreachable_food(map: &Map) -> Vec<(i32, i32)> {
+    let searches: Vec<Bfs> = map.heads().iter().map(|&(_, head)| Bfs::new(map, head)).collect();
+    (0..(map.width * map.height))
+        .map(|i| (i / map.width, i % map.width))
+        .filter(|&(y, x)| map.get(y, x) == Some(Tile::Food))
+        .filter(|&f| searches.iter().all(|bfs| bfs.distance(f).is_none()))
+        .collect()
+}
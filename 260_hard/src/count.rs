@@ -0,0 +1,92 @@
+use lib::astar::Graph;
+use map::Map;
+use search::{SnakeGraph, State, h_mst, shortest_path};
+use std::collections::HashMap;
+
+// The visited cells of a state give the cost of the moves made to reach it,
+// so the number of ways to finish from a state within the minimal cost only
+// depends on the state.
+struct Counter<'a> {
+    graph: SnakeGraph<'a>,
+    memo: HashMap<State, u64>,
+}
+
+impl<'a> Counter<'a> {
+    // Paths from the node eating all the food for exactly `left` more.
+    fn count(&mut self, node: &State, left: i32) -> u64 {
+        if self.graph.is_goal(node) {
+            return if left == 0 { 1 } else { 0 };
+        }
+        if h_mst(&self.graph, node) > left {
+            return 0;
+        }
+        if let Some(&n) = self.memo.get(node) {
+            return n;
+        }
+        let mut n: u64 = 0;
+        for ((_, y, x), next) in self.graph.neighbors(node) {
+            let cost = self.graph.map.cost(y, x);
+            if cost <= left {
+                n = n.saturating_add(self.count(&next, left - cost));
+            }
+        }
+        self.memo.insert(node.clone(), n);
+        n
+    }
+
+    // Up to `limit` of the counted paths, in move order.
+    fn list(&mut self, node: &State, left: i32, path: &mut Vec<(char, i32, i32)>,
+            limit: usize, res: &mut Vec<Vec<(char, i32, i32)>>) {
+        if res.len() >= limit {
+            return;
+        }
+        if self.graph.is_goal(node) {
+            if left == 0 {
+                res.push(path.clone());
+            }
+            return;
+        }
+        for (m, next) in self.graph.neighbors(node) {
+            let cost = self.graph.map.cost(m.1, m.2);
+            if cost <= left && self.count(&next, left - cost) > 0 {
+                path.push(m);
+                self.list(&next, left - cost, path, limit, res);
+                path.pop();
+            }
+        }
+    }
+}
+
+pub struct Solutions {
+    pub cost: i32,
+    // Saturates at u64::MAX.
+    pub count: u64,
+    pub paths: Vec<Vec<(char, i32, i32)>>,
+}
+
+// Count the solutions of minimal cost and list up to `limit` of them.
+pub fn count_solutions(map: &Map, limit: usize) -> Option<Solutions> {
+    let cost = match shortest_path(map) {
+        Some(path) => map.path_cost(&path),
+        None => return None,
+    };
+    let mut counter = Counter { graph: SnakeGraph::new(map), memo: HashMap::new() };
+    let start = counter.graph.start();
+    let count = counter.count(&start, cost);
+    let mut paths = Vec::new();
+    counter.list(&start, cost, &mut Vec::new(), limit, &mut paths);
+    Some(Solutions { cost: cost, count: count, paths: paths })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use map::from_text;
+
+    #[test]
+    fn portals_under_the_body_cannot_be_entered() {
+        let solutions = count_solutions(&from_text("+---+\n|s1*|\n|  #|\n|#1#|\n+---+"), 5).unwrap();
+        assert_eq!((solutions.cost, solutions.count), (4, 1));
+        assert_eq!(solutions.paths[0].iter().map(|&(m, _, _)| m).collect::<String>(), "↓→↓→");
+    }
+}
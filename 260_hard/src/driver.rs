@@ -0,0 +1,133 @@
+use bfs::unreachable_food;
+use map::Map;
+use multi::{Round, plan_until, print_plan};
+use render::{Theme, UNREACHABLE, write_path};
+use search::shortest_path_until;
+use std::any::Any;
+use std::collections::BTreeMap;
+use std::io;
+use std::panic::{AssertUnwindSafe, catch_unwind};
+use std::sync::mpsc::channel;
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+
+enum Outcome {
+    Unreachable(Vec<(i32, i32)>),
+    Path(Vec<(char, i32, i32)>),
+    Plan(Vec<Round>),
+    NoSolution,
+    // The cost no solution is below when the budget ran out.
+    TimedOut(i32),
+    // The solver panicked, with its message.
+    Failed(String),
+}
+
+fn solve(map: &Map, budget: Duration) -> Outcome {
+    let unreachable = unreachable_food(map);
+    if !unreachable.is_empty() {
+        return Outcome::Unreachable(unreachable);
+    }
+    let start = Instant::now();
+    let stop = || start.elapsed() > budget;
+    if map.heads().len() > 1 && map.hazards.is_empty() {
+        match plan_until(map, stop) {
+            Ok(Some(rounds)) => Outcome::Plan(rounds),
+            Ok(None) => Outcome::NoSolution,
+            Err(depth) => Outcome::TimedOut(depth),
+        }
+    } else {
+        match shortest_path_until(map, stop) {
+            Ok(Some(path)) => Outcome::Path(path),
+            Ok(None) => Outcome::NoSolution,
+            Err(depth) => Outcome::TimedOut(depth),
+        }
+    }
+}
+
+fn panic_message(e: Box<Any + Send>) -> String {
+    match e.downcast::<String>() {
+        Ok(s) => *s,
+        Err(e) => e.downcast_ref::<&str>().map_or("unknown error".to_string(), |s| s.to_string()),
+    }
+}
+
+fn print(n: usize, map: &Map, outcome: &Outcome, elapsed: Duration, theme: &Theme) {
+    println!("\nMap: {}", n);
+    write_path(&mut io::stdout(), map, &[], theme).unwrap();
+    println!("{}x{}: {} (h: {:?})", map.height, map.width, map.food, map.head);
+    match *outcome {
+        Outcome::Unreachable(ref cells) => {
+            let list: Vec<String> = cells.iter().map(|&(y, x)| format!("({}, {})", y, x)).collect();
+            println!("Unreachable food at {}, skipping:", list.join(", "));
+            let marks: Vec<_> = cells.iter().map(|&(y, x)| (UNREACHABLE, y, x)).collect();
+            write_path(&mut io::stdout(), map, &marks, theme).unwrap();
+        },
+        Outcome::Path(ref path) => {
+            println!("{} moves", map.path_cost(path));
+            write_path(&mut io::stdout(), map, path, theme).unwrap();
+        },
+        Outcome::Plan(ref rounds) => print_plan(map, rounds, theme),
+        Outcome::NoSolution => println!("No solution"),
+        Outcome::TimedOut(depth) => println!("Timed out, no solution below {} moves", depth),
+        Outcome::Failed(ref msg) => println!("Solver failed: {}", msg),
+    }
+    println!("Solved in {} ms", millis(elapsed));
+}
+
+fn millis(d: Duration) -> u64 {
+    d.as_secs() * 1000 + d.subsec_nanos() as u64 / 1_000_000
+}
+
+// Solve the maps on `threads` threads, giving up on a map after `budget`.
+// Results are printed in input order as soon as the previous ones are done,
+// followed by the list of the maps that timed out.
+pub fn solve_all(maps: Vec<Map>, threads: usize, budget: Duration, theme: &Theme) {
+    let maps = Arc::new(maps);
+    let next = Arc::new(Mutex::new(0));
+    let (tx, rx) = channel();
+    for _ in 0..threads {
+        let (maps, next, tx) = (maps.clone(), next.clone(), tx.clone());
+        thread::spawn(move || {
+            loop {
+                let n = {
+                    let mut next = next.lock().unwrap();
+                    *next += 1;
+                    *next - 1
+                };
+                if n >= maps.len() {
+                    return;
+                }
+                let start = Instant::now();
+                // A map the solver panics on must still be sent, or the
+                // maps after it would never be printed.
+                let outcome = catch_unwind(AssertUnwindSafe(|| solve(&maps[n], budget)))
+                    .unwrap_or_else(|e| Outcome::Failed(panic_message(e)));
+                if tx.send((n, outcome, start.elapsed())).is_err() {
+                    return;
+                }
+            }
+        });
+    }
+    drop(tx);
+
+    let mut done = BTreeMap::new();
+    let mut printed = 0;
+    let mut timed_out = Vec::new();
+    for (n, outcome, elapsed) in rx {
+        done.insert(n, (outcome, elapsed));
+        while let Some((outcome, elapsed)) = done.remove(&printed) {
+            print(printed, &maps[printed], &outcome, elapsed, theme);
+            if let Outcome::TimedOut(depth) = outcome {
+                timed_out.push((printed, depth));
+            }
+            printed += 1;
+        }
+    }
+    if !timed_out.is_empty() {
+        println!("\nTimed out after {} ms:", millis(budget));
+        for (n, depth) in timed_out {
+            println!("Map {}: depth {} reached", n, depth);
+        }
+    }
+}
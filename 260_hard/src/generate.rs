@@ -0,0 +1,220 @@
+use bfs::Bfs;
+use map::{Map, Tile, write_maps};
+use rand::{Rng, SeedableRng, XorShiftRng};
+use search::shortest_path_until;
+use std::io;
+use std::time::{Duration, Instant};
+
+fn create_rng(seed: u32) -> XorShiftRng {
+    XorShiftRng::from_seed([seed, seed ^ 0x9e37_79b9, 0x243f_6a88, 0x85a3_08d3])
+}
+
+// Scatter pits with the given density (in percent), put the head on a free
+// cell and the food on cells reachable from it. Returns None when there is
+// not enough room.
+fn random_map<R: Rng>(rng: &mut R, width: i32, height: i32, density: u32, food: usize) -> Option<Map> {
+    let mut map = Map::filled(width, height, Tile::Empty);
+    for y in 0..height {
+        for x in 0..width {
+            if rng.gen_range(0, 100) < density {
+                map.set(y, x, Tile::Pit);
+            }
+        }
+    }
+    let free: Vec<(i32, i32)> = (0..(width * height))
+        .map(|i| (i / width, i % width))
+        .filter(|&(y, x)| map.get(y, x) == Some(Tile::Empty))
+        .collect();
+    if free.is_empty() {
+        return None;
+    }
+    map.head = free[rng.gen_range(0, free.len())];
+    map.set(map.head.0, map.head.1, Tile::Snake);
+
+    let bfs = Bfs::new(&map, map.head);
+    let mut reachable: Vec<(i32, i32)> = free.into_iter()
+        .filter(|&p| p != map.head && bfs.distance(p).is_some())
+        .collect();
+    if reachable.len() < food {
+        return None;
+    }
+    rng.shuffle(&mut reachable);
+    for &(y, x) in &reachable[..food] {
+        map.set(y, x, Tile::Food);
+    }
+    map.food = food as i32;
+    Some(map)
+}
+
+// "map <n>", or just the number, or nothing, whatever fits in the border.
+fn title(n: usize, width: usize) -> String {
+    let long = format!("map {}", n);
+    let short = n.to_string();
+    if long.len() <= width {
+        long
+    } else if short.len() <= width {
+        short
+    } else {
+        String::new()
+    }
+}
+
+// Random maps tried for each generated map before giving up.
+const MAX_ATTEMPTS: usize = 1000;
+// How long the solver may take on a random map, in milliseconds.
+const SOLVE_BUDGET: u64 = 1000;
+
+// Only keep maps the solver can clear, since reachable food can still be
+// impossible to eat in a single path that never crosses itself. A map the
+// solver does not clear within SOLVE_BUDGET counts as a failed attempt, so
+// a slow machine may pick other maps for the same seed. Gives up with the
+// maps found so far when none of MAX_ATTEMPTS random maps work.
+pub fn generate(width: i32, height: i32, density: u32, food: usize, count: usize, seed: u32) -> Result<Vec<Map>, Vec<Map>> {
+    let mut rng = create_rng(seed);
+    let mut maps = Vec::new();
+    let mut attempts = 0;
+
+    while maps.len() < count {
+        if attempts == MAX_ATTEMPTS {
+            return Err(maps);
+        }
+        attempts += 1;
+        if let Some(mut map) = random_map(&mut rng, width, height, density, food) {
+            let start = Instant::now();
+            let budget = Duration::from_millis(SOLVE_BUDGET);
+            if let Ok(Some(_)) = shortest_path_until(&map, || start.elapsed() > budget) {
+                map.title = title(maps.len() + 1, width as usize);
+                maps.push(map);
+                attempts = 0;
+            }
+        }
+    }
+    Ok(maps)
+}
+
+fn usage() -> ! {
+    println!("Usage: 260_hard generate <width> <height> <pit %> <food> <count> [seed]");
+    ::std::process::exit(1);
+}
+
+pub fn run(args: &[String]) {
+    let nums: Vec<u32> = args.iter().map(|a| a.parse::<u32>().unwrap_or_else(|_| usage())).collect();
+    if nums.len() < 5 || nums.len() > 6 || nums[0] == 0 || nums[1] == 0 || nums[2] >= 100 {
+        usage();
+    }
+    // The head takes a cell.
+    if nums[3] >= nums[0].saturating_mul(nums[1]) {
+        println!("No room for {} food and the head on a {}x{} map", nums[3], nums[0], nums[1]);
+        ::std::process::exit(1);
+    }
+    let seed = if nums.len() == 6 { nums[5] } else { 0 };
+    match generate(nums[0] as i32, nums[1] as i32, nums[2], nums[3] as usize, nums[4] as usize, seed) {
+        Ok(maps) => write_maps(&mut io::stdout(), &maps).unwrap(),
+        Err(maps) => {
+            println!("Gave up after {} random maps without a solvable one, {} of {} generated",
+                     MAX_ATTEMPTS, maps.len(), nums[4]);
+            ::std::process::exit(1);
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use map::{Hazard, parse_maps};
+
+    fn round_trip(maps: &[Map]) -> Vec<Map> {
+        let mut out = Vec::new();
+        write_maps(&mut out, maps).unwrap();
+        parse_maps(String::from_utf8(out).unwrap().lines().map(|l| l.to_string()).collect()).unwrap()
+    }
+
+    const TITLE_CHARS: [char; 11] = ['a', 'b', '1', ' ', '-', '[', ']', '+', '|', '*', '#'];
+    const TILES: [Tile; 6] = [Tile::Wall, Tile::Mud, Tile::OneWay(-1, 0), Tile::OneWay(1, 0),
+                              Tile::OneWay(0, -1), Tile::OneWay(0, 1)];
+
+    // A random map with portals, walls, mud, one-way tiles, options, hazards
+    // and a random title, which may not be one that can be written.
+    fn random_full_map<R: Rng>(rng: &mut R) -> Option<Map> {
+        let width = rng.gen_range(1, 15);
+        let height = rng.gen_range(1, 8);
+        let density = rng.gen_range(0, 40);
+        let food = rng.gen_range(0, 5);
+        let mut map = match random_map(rng, width, height, density, food) {
+            Some(map) => map,
+            None => return None,
+        };
+        let mut empty: Vec<(i32, i32)> = (0..(width * height))
+            .map(|i| (i / width, i % width))
+            .filter(|&(y, x)| map.get(y, x) == Some(Tile::Empty))
+            .collect();
+        rng.shuffle(&mut empty);
+        for &c in &['1', '2'] {
+            if empty.len() >= 2 && rng.gen() {
+                let (a, b) = (empty.pop().unwrap(), empty.pop().unwrap());
+                map.add_portal(c, a, b);
+            }
+        }
+        for &(y, x) in &empty {
+            match rng.gen_range(0, 8) {
+                0 | 1 => map.set(y, x, *rng.choose(&TILES).unwrap()),
+                2 => {
+                    let period = rng.gen_range(1, 6);
+                    map.hazards.push(Hazard::Pit((y, x), period, rng.gen_range(0, period + 1)));
+                },
+                _ => {},
+            }
+        }
+        for y in 0..height {
+            for x in 0..width {
+                if map.get(y, x) == Some(Tile::Food) && rng.gen() {
+                    map.hazards.push(Hazard::Expires((y, x), rng.gen_range(0, 50)));
+                }
+            }
+        }
+        map.wrap = rng.gen();
+        map.diagonal = rng.gen();
+        let len = rng.gen_range(0, 8);
+        map.title = (0..len).map(|_| *rng.choose(&TITLE_CHARS).unwrap()).collect();
+        Some(map)
+    }
+
+    // Maps are either refused by the writer or read back the same.
+    #[test]
+    fn random_maps_read_back_the_same() {
+        let mut written = 0;
+        for seed in 0..1000 {
+            let mut rng = create_rng(seed);
+            if let Some(map) = random_full_map(&mut rng) {
+                let maps = vec![map];
+                if write_maps(&mut Vec::new(), &maps).is_ok() {
+                    assert_eq!(round_trip(&maps), maps, "seed {}", seed);
+                    written += 1;
+                }
+            }
+        }
+        assert!(written > 100, "only {} maps written", written);
+    }
+
+    #[test]
+    fn generated_maps_read_back_the_same() {
+        for &(width, height) in &[(3, 3), (1, 4), (10, 2)] {
+            let maps = generate(width, height, 10, 1, 12, 5).unwrap();
+            assert_eq!(round_trip(&maps), maps);
+        }
+    }
+
+    #[test]
+    fn generated_titles_are_distinct() {
+        let maps = generate(3, 3, 0, 1, 2, 5).unwrap();
+        assert_eq!(maps[0].title, "1");
+        assert_eq!(maps[1].title, "2");
+    }
+
+    #[test]
+    fn unsolvable_settings_give_up() {
+        // Hardly any cell is free of pits.
+        let maps = generate(3, 3, 99, 1, 1, 0).unwrap_err();
+        assert!(maps.is_empty());
+    }
+}
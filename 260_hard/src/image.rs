@@ -0,0 +1,125 @@
+use map::{Map, Tile};
+use std::collections::HashSet;
+use std::fs::File;
+use std::io;
+use std::io::prelude::*;
+use std::path::Path;
+
+// Side of a cell, in SVG units and in PPM pixels.
+const CELL: i32 = 20;
+const PIXELS: i32 = 8;
+
+type Rgb = (u8, u8, u8);
+
+const PATH: Rgb = (60, 170, 60);
+
+fn colour(tile: Tile) -> Rgb {
+    match tile {
+        Tile::Empty => (255, 255, 255),
+        Tile::Snake | Tile::Head(_) => (40, 80, 200),
+        Tile::Food => (220, 50, 50),
+        Tile::Pit => (0, 0, 0),
+        Tile::Wall => (120, 120, 120),
+        Tile::Portal(_) => (150, 60, 180),
+        Tile::OneWay(_, _) => (150, 190, 230),
+        Tile::Mud => (140, 100, 50),
+    }
+}
+
+fn hex((r, g, b): Rgb) -> String {
+    format!("#{:02x}{:02x}{:02x}", r, g, b)
+}
+
+// The paths of the snakes, each from its head, split where they jump
+// (portals, wrapping edges), as lists of cells.
+fn segments(map: &Map, paths: &[Vec<(char, i32, i32)>]) -> Vec<Vec<(i32, i32)>> {
+    let mut res = Vec::new();
+    for (&(_, head), path) in map.heads().iter().zip(paths) {
+        res.push(vec![head]);
+        for &(_, y, x) in path {
+            let &(py, px) = res.last().unwrap().last().unwrap();
+            if (y - py).abs() > 1 || (x - px).abs() > 1 {
+                res.push(Vec::new());
+            }
+            res.last_mut().unwrap().push((y, x));
+        }
+    }
+    res
+}
+
+// `paths` holds the path of every snake, in the order of Map::heads.
+pub fn write_svg<W: Write>(out: &mut W, map: &Map, paths: &[Vec<(char, i32, i32)>]) -> io::Result<()> {
+    try!(writeln!(out, "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{}\" height=\"{}\">",
+                  map.width * CELL, map.height * CELL));
+    try!(writeln!(out, "  <defs>"));
+    try!(writeln!(out, "    <marker id=\"arrow\" viewBox=\"0 0 10 10\" refX=\"5\" refY=\"5\" \
+                        markerWidth=\"4\" markerHeight=\"4\" orient=\"auto\">"));
+    try!(writeln!(out, "      <path d=\"M0,0 L10,5 L0,10 z\" fill=\"{}\"/>", hex(PATH)));
+    try!(writeln!(out, "    </marker>"));
+    try!(writeln!(out, "  </defs>"));
+    for y in 0..map.height {
+        for x in 0..map.width {
+            let tile = map.get(y, x).unwrap();
+            try!(writeln!(out, "  <rect x=\"{}\" y=\"{}\" width=\"{}\" height=\"{}\" fill=\"{}\" stroke=\"#dddddd\"/>",
+                          x * CELL, y * CELL, CELL, CELL, hex(colour(tile))));
+            if let Tile::Portal(c) = tile {
+                try!(writeln!(out, "  <text x=\"{}\" y=\"{}\" font-size=\"{}\" text-anchor=\"middle\" fill=\"white\">{}</text>",
+                              x * CELL + CELL / 2, y * CELL + CELL * 3 / 4, CELL * 2 / 3, c));
+            }
+        }
+    }
+    for segment in segments(map, paths) {
+        if segment.len() < 2 {
+            continue;
+        }
+        let points: Vec<String> = segment.iter()
+            .map(|&(y, x)| format!("{},{}", x * CELL + CELL / 2, y * CELL + CELL / 2))
+            .collect();
+        try!(writeln!(out, "  <polyline points=\"{}\" fill=\"none\" stroke=\"{}\" stroke-width=\"{}\" \
+                            marker-mid=\"url(#arrow)\" marker-end=\"url(#arrow)\"/>",
+                      points.join(" "), hex(PATH), CELL / 5));
+    }
+    writeln!(out, "</svg>")
+}
+
+// A plain (ASCII) PPM with the cells of the path painted over the map.
+pub fn write_ppm<W: Write>(out: &mut W, map: &Map, paths: &[Vec<(char, i32, i32)>]) -> io::Result<()> {
+    let on_path: HashSet<(i32, i32)> = paths.iter().flat_map(|p| p.iter().map(|&(_, y, x)| (y, x))).collect();
+    try!(writeln!(out, "P3\n{} {}\n255", map.width * PIXELS, map.height * PIXELS));
+    for py in 0..(map.height * PIXELS) {
+        let mut row = Vec::new();
+        for px in 0..(map.width * PIXELS) {
+            let (y, x) = (py / PIXELS, px / PIXELS);
+            let border = py % PIXELS == 0 || px % PIXELS == 0;
+            let (r, g, b) = if on_path.contains(&(y, x)) && !border {
+                PATH
+            } else {
+                colour(map.get(y, x).unwrap())
+            };
+            row.push(format!("{} {} {}", r, g, b));
+        }
+        try!(writeln!(out, "{}", row.join(" ")));
+    }
+    Ok(())
+}
+
+// Write map-<n>.svg and map-<n>.ppm in the directory.
+pub fn export(dir: &Path, n: usize, map: &Map, paths: &[Vec<(char, i32, i32)>]) -> io::Result<()> {
+    let mut svg = try!(File::create(dir.join(format!("map-{}.svg", n))));
+    try!(write_svg(&mut svg, map, paths));
+    let mut ppm = try!(File::create(dir.join(format!("map-{}.ppm", n))));
+    write_ppm(&mut ppm, map, paths)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use map::from_text;
+
+    #[test]
+    fn every_snake_path_starts_from_its_head() {
+        let map = from_text("+----+\n|s *t|\n|   *|\n+----+");
+        let paths = vec![vec![('→', 0, 1), ('→', 0, 2)], vec![('↓', 1, 3)]];
+        assert_eq!(segments(&map, &paths), vec![vec![(0, 0), (0, 1), (0, 2)], vec![(0, 3), (1, 3)]]);
+    }
+}
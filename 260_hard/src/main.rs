@@ -1,22 +1,52 @@
+extern crate rand;
+
+use std::cmp;
 use std::env;
+use std::fs;
+use std::io;
+use std::path::Path;
+use std::time::Duration;
 
+mod lib;
+mod animate;
+mod bfs;
+mod count;
+mod driver;
+mod generate;
+mod image;
 mod map;
+mod multi;
+mod render;
+mod search;
+mod snake;
+mod tour;
 
+use animate::{Animation, Frame, trail};
+use bfs::unreachable_food;
+use count::count_solutions;
+use lib::astar::a_star_weighted;
 use map::Map;
 use map::Tile;
-use map::parse_input_file;
+use map::{parse_input_file, write_maps};
+use multi::{Round, paths, plan, print_plan};
+use render::{Theme, UNREACHABLE, Unicode, theme, write_path};
+use search::{moves, shortest_path};
+use snake::{BodyGraph, h_body, map_at, replay};
+use std::collections::HashSet;
+use tour::{MAX_FOOD, held_karp};
 
 
 fn find_path(map: &mut Map, depth: i32) -> Option<Vec<(char, i32, i32)>> {
     if depth == 0 {
         return None
     }
-    for &(m, dy, dx) in &[('↑', -1, 0), ('↓', 1, 0), ('←', 0, -1), ('→', 0, 1)] {
+    for &(m, dy, dx) in moves(map) {
         let head = map.head;
-        let y = map.head.0 + dy;
-        let x = map.head.1 + dx;
-        if let Some(t) = map.get(y, x) {
-            if t == Tile::Pit || t == Tile::Snake {
+        // A portal the snake landed on became a snake tile, so it is never
+        // entered again.
+        if let Some(((y, x), _)) = map.step(head.0, head.1, dy, dx) {
+            let t = map.get(y, x).unwrap();
+            if t == Tile::Snake {
                 continue;
             }
             if t == Tile::Food {
@@ -42,24 +72,296 @@ fn find_path(map: &mut Map, depth: i32) -> Option<Vec<(char, i32, i32)>> {
     None
 }
 
+fn iterative_deepening(map: &Map) -> Option<Vec<(char, i32, i32)>> {
+    for d in 0..(map.height * map.width) {
+        let mut map = map.clone();
+        if let Some(mut path) = find_path(&mut map, d) {
+            path.reverse();
+            return Some(path);
+        }
+    }
+    None
+}
+
+// Solve with game rules, printing the snake after every move. Returns the
+// path found, as the other modes below do.
+fn play(map: &Map, length: usize, theme: &Theme, animation: Option<&mut Animation>) -> Option<Vec<(char, i32, i32)>> {
+    let graph = BodyGraph::new(map, length);
+    match a_star_weighted(&graph, graph.start(), h_body, |g, _, &(_, y, x)| g.map.cost(y, x)) {
+        (_, Some(path)) => {
+            let moves: Vec<_> = path.iter().map(|&(m, _)| m).collect();
+            let bodies = replay(&graph, &moves);
+            if let Some(animation) = animation {
+                let frames: Vec<Frame> = moves.iter().zip(bodies.iter())
+                    .map(|(&m, body)| Frame { map: map_at(&graph, body), moves: vec![m] })
+                    .collect();
+                animation.play(map, &frames, theme);
+                return Some(moves);
+            }
+            for (&m, body) in moves.iter().zip(bodies.iter()) {
+                println!("{} to ({}, {}), length {}", m.0, m.1, m.2, body.cells.len());
+                write_path(&mut io::stdout(), &map_at(&graph, body), &[m], theme).unwrap();
+            }
+            println!("{} moves", map.path_cost(&moves));
+            Some(moves)
+        },
+        (_, None) => {
+            println!("No solution");
+            None
+        },
+    }
+}
+
+// Plan the rounds of all the snakes, each drawn with its own letter.
+fn herd(map: &Map, theme: &Theme) -> Option<Vec<Round>> {
+    let rounds = plan(map);
+    match rounds {
+        Some(ref rounds) => print_plan(map, rounds, theme),
+        None => println!("No solution"),
+    }
+    rounds
+}
+
+// Count the shortest solutions, printing up to `limit` of them. Returns the
+// first one even when none are printed.
+fn count(map: &Map, limit: usize, theme: &Theme) -> Option<Vec<(char, i32, i32)>> {
+    match count_solutions(map, cmp::max(limit, 1)) {
+        Some(solutions) => {
+            if solutions.count == 1 {
+                println!("Unique solution of {} moves", solutions.cost);
+            } else if solutions.count == ::std::u64::MAX {
+                println!("At least {} solutions of {} moves", solutions.count, solutions.cost);
+            } else {
+                println!("{} solutions of {} moves", solutions.count, solutions.cost);
+            }
+            for (i, path) in solutions.paths.iter().take(limit).enumerate() {
+                let moves: String = path.iter().map(|&(m, _, _)| m).collect();
+                println!("{}: {}", i + 1, moves);
+                write_path(&mut io::stdout(), map, path, theme).unwrap();
+            }
+            solutions.paths.into_iter().next()
+        },
+        None => {
+            println!("No solution");
+            None
+        },
+    }
+}
+
+// Whether the path enters a cell twice or goes back to the head.
+fn crosses(map: &Map, path: &[(char, i32, i32)]) -> bool {
+    let cells: HashSet<(i32, i32)> = path.iter().map(|&(_, y, x)| (y, x)).collect();
+    cells.len() < path.len() || cells.contains(&map.head)
+}
+
+// The Held-Karp tour, which may cross itself.
+fn tour(map: &Map, theme: &Theme) -> Option<Vec<(char, i32, i32)>> {
+    if map.food as usize > MAX_FOOD {
+        println!("Too much food for Held-Karp, at most {}", MAX_FOOD);
+        return None;
+    }
+    match held_karp(map) {
+        Some(tour) => {
+            let order: Vec<String> = tour.order.iter().map(|&(y, x)| format!("({}, {})", y, x)).collect();
+            println!("Food order: {}", order.join(", "));
+            let crossing = crosses(map, &tour.path);
+            let moves = map.path_cost(&tour.path);
+            write_path(&mut io::stdout(), map, &tour.path, theme).unwrap();
+            if crossing {
+                println!("{} moves, the path crosses itself", moves);
+            } else {
+                println!("{} moves", moves);
+            }
+            Some(tour.path)
+        },
+        None => {
+            println!("No solution");
+            None
+        },
+    }
+}
+
 fn main() {
-    let path = env::args().nth(1).expect("Missing arg");
-    let maps = parse_input_file(path.as_str()).expect("File not readable");
+    let args: Vec<String> = env::args().skip(1).collect();
+    if args.first().map_or(false, |a| a == "generate") {
+        generate::run(&args[1..]);
+        return;
+    }
+    let iddfs = args.iter().any(|a| a == "--iddfs");
+    let held_karp = args.iter().any(|a| a == "--held-karp");
+    let length = args.iter().position(|a| a == "--length")
+        .map(|p| args.get(p + 1).and_then(|l| l.parse::<usize>().ok()).and_then(|l| if l > 0 { Some(l) } else { None })
+             .expect("Invalid length"));
+    let theme = match args.iter().position(|a| a == "--theme") {
+        Some(p) => args.get(p + 1).and_then(|t| theme(t)).expect("Invalid theme"),
+        None => Box::new(Unicode),
+    };
+    let delay = args.iter().position(|a| a == "--delay")
+        .map_or(200, |p| args.get(p + 1).and_then(|d| d.parse::<u64>().ok()).expect("Invalid delay"));
+    let mut animation = if args.iter().any(|a| a == "--animate") { Some(Animation::new(delay)) } else { None };
+    let export = args.iter().position(|a| a == "--export")
+        .map(|p| Path::new(args.get(p + 1).expect("Missing export directory")));
+    if let Some(dir) = export {
+        fs::create_dir_all(dir).expect("Cannot create export directory");
+    }
+    let threads = args.iter().position(|a| a == "--threads")
+        .map(|p| args.get(p + 1).and_then(|t| t.parse::<usize>().ok()).and_then(|t| if t > 0 { Some(t) } else { None })
+             .expect("Invalid thread count"));
+    let timeout = args.iter().position(|a| a == "--timeout")
+        .map(|p| args.get(p + 1).and_then(|t| t.parse::<u64>().ok()).expect("Invalid timeout"));
+    let list = args.iter().position(|a| a == "--list")
+        .map(|p| args.get(p + 1).and_then(|l| l.parse::<usize>().ok()).expect("Invalid list limit"));
+    let counting = list.is_some() || args.iter().any(|a| a == "--count");
+    let value_flags = ["--length", "--theme", "--delay", "--export", "--threads", "--timeout", "--list"];
+    let path = args.iter().enumerate()
+        .find(|&(i, a)| !a.starts_with("--") && (i == 0 || !value_flags.contains(&args[i - 1].as_str())))
+        .map(|(_, a)| a)
+        .expect("Missing arg");
+    let mut maps = match parse_input_file(path.as_str()) {
+        Ok(maps) => maps,
+        Err(e) => { println!("{}: {}", path, e); return }
+    };
+    // Options given on the command line apply to every map, on top of the
+    // ones in the map titles.
+    for map in &mut maps {
+        map.wrap |= args.iter().any(|a| a == "--wrap");
+        map.diagonal |= args.iter().any(|a| a == "--diagonal");
+    }
+    if args.iter().any(|a| a == "--write") {
+        if let Err(e) = write_maps(&mut io::stdout(), &maps) {
+            println!("\nCannot write the maps: {}", e);
+        }
+        return;
+    }
+    if threads.is_some() || timeout.is_some() {
+        // The driver only finds shortest paths and multi-snake plans.
+        let unsupported: Vec<&str> = ["--count", "--list", "--length", "--held-karp", "--iddfs", "--export", "--animate"]
+            .iter().cloned().filter(|f| args.iter().any(|a| a == f)).collect();
+        if !unsupported.is_empty() {
+            println!("--threads and --timeout cannot be used with {}", unsupported.join(", "));
+            return;
+        }
+        let budget = timeout.map_or(Duration::new(::std::u64::MAX, 0), Duration::from_millis);
+        driver::solve_all(maps, threads.unwrap_or(4), budget, &*theme);
+        return;
+    }
 
     for (n, m) in maps.iter().enumerate() {
         let map = m.clone();
         println!("\nMap: {}", n);
-        map.print();
+        write_path(&mut io::stdout(), &map, &[], &*theme).unwrap();
         println!("{}x{}: {} (h: {:?})", map.height, map.width, map.food, map.head);
 
-        for d in 0..(map.height * map.width) {
-            let mut map = map.clone();
-            let res = find_path(&mut map, d);
-            if let Some(mut path) = res {
-                path.reverse();
-                map.print_with_path(path);
-                break;
+        let unreachable = unreachable_food(&map);
+        if !unreachable.is_empty() {
+            let cells: Vec<String> = unreachable.iter().map(|&(y, x)| format!("({}, {})", y, x)).collect();
+            println!("Unreachable food at {}, skipping:", cells.join(", "));
+            let marks: Vec<_> = unreachable.iter().map(|&(y, x)| (UNREACHABLE, y, x)).collect();
+            write_path(&mut io::stdout(), &map, &marks, &*theme).unwrap();
+            if let Some(dir) = export {
+                image::export(dir, n, &map, &[]).expect("Cannot write images");
+            }
+            continue;
+        }
+
+        // The paths to export, one per snake.
+        let export_paths = |paths: &[Vec<(char, i32, i32)>]| {
+            if let Some(dir) = export {
+                image::export(dir, n, &map, paths).expect("Cannot write images");
+            }
+        };
+        // Only the default solver plans over time.
+        let timed = !map.hazards.is_empty();
+        if timed && (map.heads().len() > 1 || length.is_some() || held_karp || iddfs) {
+            println!("Time-dependent tiles need the default solver, using it");
+        }
+        if map.heads().len() > 1 && !timed {
+            let rounds = herd(&map, &*theme);
+            export_paths(&rounds.map_or(vec![], |r| paths(&map, &r)));
+            continue;
+        }
+        if let Some(length) = length.and_then(|l| if timed { None } else { Some(l) }) {
+            let res = play(&map, length, &*theme, animation.as_mut());
+            export_paths(&res.into_iter().collect::<Vec<_>>());
+            continue;
+        }
+        if held_karp && !timed {
+            let res = tour(&map, &*theme);
+            export_paths(&res.into_iter().collect::<Vec<_>>());
+            continue;
+        }
+        if counting {
+            let res = count(&map, list.unwrap_or(0), &*theme);
+            export_paths(&res.into_iter().collect::<Vec<_>>());
+            continue;
+        }
+        let res = if iddfs && !timed { iterative_deepening(&map) } else { shortest_path(&map) };
+        export_paths(&res.iter().cloned().collect::<Vec<_>>());
+        match res {
+            Some(path) => {
+                if let Some(ref mut animation) = animation {
+                    animation.play(&map, &trail(&map, &path), &*theme);
+                    continue;
+                }
+                println!("{} moves", map.path_cost(&path));
+                write_path(&mut io::stdout(), &map, &path, &*theme).unwrap();
+                if timed {
+                    let mut time = 0;
+                    for (frame, &(_, y, x)) in trail(&map, &path).iter().zip(path.iter()) {
+                        time += map.cost(y, x);
+                        println!("Time {}:", time);
+                        write_path(&mut io::stdout(), &frame.map, &frame.moves, &*theme).unwrap();
+                    }
+                }
+            },
+            None => println!("No solution"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use map::from_text;
+
+    const MAPS: [&'static str; 5] = [
+        "+----+\n|*s *|\n+----+",
+        "+---+\n|* *|\n| s |\n|* *|\n+---+",
+        "+----+\n|*  *|\n|s## |\n|*  *|\n+----+",
+        "+-----+\n|* * *|\n|  s  |\n|*   *|\n+-----+",
+        "+---+\n|*#*|\n| s |\n|*#*|\n+---+",
+    ];
+
+    // Held-Karp may cross the path, which find_path cannot do, so it is
+    // never worse and only better when it does.
+    #[test]
+    fn held_karp_is_never_worse_than_find_path() {
+        for text in MAPS.iter() {
+            let map = from_text(text);
+            let tour = held_karp(&map).unwrap();
+            let tour_cost = map.path_cost(&tour.path);
+            match iterative_deepening(&map) {
+                Some(path) => {
+                    let cost = map.path_cost(&path);
+                    assert!(tour_cost <= cost, "{}: {} > {}", text, tour_cost, cost);
+                    if !crosses(&map, &tour.path) {
+                        assert_eq!(tour_cost, cost, "{}", text);
+                    }
+                },
+                None => assert!(crosses(&map, &tour.path), "{}", text),
             }
         }
     }
+
+    // A* with the MST heuristic finds paths as short as the exhaustive
+    // find_path, or none when find_path finds none.
+    #[test]
+    fn shortest_path_matches_find_path() {
+        let input = map::parse_maps(include_str!("../input.txt").lines().map(|l| l.to_string()).collect()).unwrap();
+        let fixtures = MAPS.iter().map(|text| from_text(text));
+        for map in fixtures.chain(input.into_iter().take(4)) {
+            let cost = shortest_path(&map).map(|p| map.path_cost(&p));
+            assert_eq!(cost, iterative_deepening(&map).map(|p| map.path_cost(&p)), "{}", map.title);
+        }
+    }
 }
@@ -2,12 +2,15 @@ use std::io;
 use std::io::prelude::*;
 use std::io::BufReader;
 use std::fs::File;
-use std::collections::HashMap;
+use std::collections::BTreeMap;
+use std::fmt;
+use std::iter;
 
 #[derive(Debug)]
 pub enum ParseError {
     Io(io::Error),
-    Format(String),
+    // Line and column, both starting from 1, and what is wrong there.
+    Format(usize, usize, String),
 }
 
 impl From<io::Error> for ParseError {
@@ -16,32 +19,112 @@ impl From<io::Error> for ParseError {
     }
 }
 
-impl From<String> for ParseError {
-    fn from(err: String) -> ParseError {
-        ParseError::Format(err)
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            ParseError::Io(ref err) => write!(f, "{}", err),
+            ParseError::Format(line, col, ref msg) => write!(f, "line {}, column {}: {}", line, col, msg),
+        }
     }
 }
 
+pub const MUD_COST: i32 = 2;
+
+// The letters of the heads, one per snake. 'v' is a one-way tile.
+pub const MAX_SNAKES: usize = 7;
+pub const SNAKES: [char; MAX_SNAKES] = ['s', 't', 'u', 'w', 'x', 'y', 'z'];
+
 #[derive(Copy, Clone, Debug, Eq, PartialEq)]
 pub enum Tile {
     Food,
     Empty,
     Pit,
     Snake,
+    // The head of another snake than 's'.
+    Head(char),
+    Wall,
+    // Entering a portal moves the head to the other portal with the same digit.
+    Portal(char),
+    // Can only be entered moving in the direction (dy, dx).
+    OneWay(i32, i32),
+    // Entering mud costs MUD_COST moves.
+    Mud,
+}
+
+impl Tile {
+    pub fn from_char(c: char) -> Option<Tile> {
+        match c {
+            ' ' => Some(Tile::Empty),
+            's' => Some(Tile::Snake),
+            '*' => Some(Tile::Food),
+            'O' => Some(Tile::Pit),
+            '#' => Some(Tile::Wall),
+            '^' => Some(Tile::OneWay(-1, 0)),
+            'v' => Some(Tile::OneWay(1, 0)),
+            '<' => Some(Tile::OneWay(0, -1)),
+            '>' => Some(Tile::OneWay(0, 1)),
+            '~' => Some(Tile::Mud),
+            c if c != 's' && SNAKES.contains(&c) => Some(Tile::Head(c)),
+            c if c.is_digit(10) => Some(Tile::Portal(c)),
+            _ => None,
+        }
+    }
+
+    pub fn to_char(&self) -> char {
+        match *self {
+            Tile::Empty => ' ',
+            Tile::Snake => 's',
+            Tile::Head(c) => c,
+            Tile::Food => '*',
+            Tile::Pit => 'O',
+            Tile::Wall => '#',
+            Tile::Portal(c) => c,
+            Tile::OneWay(-1, _) => '^',
+            Tile::OneWay(1, _) => 'v',
+            Tile::OneWay(_, -1) => '<',
+            Tile::OneWay(_, _) => '>',
+            Tile::Mud => '~',
+        }
+    }
 }
 
-#[derive(Clone)]
+// Tiles changing with the time, that is the cost of the moves made so far.
+// They are declared after the bottom border of the map, one per line:
+// "pit <row> <col> <period> <open>" or "food <row> <col> <moves>".
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Hazard {
+    // A pit during the first `open` moves of every `period` moves.
+    Pit((i32, i32), i32, i32),
+    // Food that is gone after that many moves.
+    Expires((i32, i32), i32),
+}
+
+#[derive(Clone, Debug, PartialEq)]
 pub struct Map {
     data: Vec<Tile>,
+    pub title: String,
+    portals: BTreeMap<(i32, i32), (i32, i32)>,
     pub head: (i32, i32),
     pub width: i32,
     pub height: i32,
     pub food: i32,
+    // Moving off an edge comes back on the opposite one.
+    pub wrap: bool,
+    // The snake can also move diagonally.
+    pub diagonal: bool,
+    pub hazards: Vec<Hazard>,
 }
 
 impl Map {
     pub fn new(width: i32, height: i32) -> Map {
-        Map { data: vec![], width: width, height: height, head: (0, 0), food: 0 }
+        Map {
+            data: vec![], title: String::new(), portals: BTreeMap::new(), width: width, height: height,
+            head: (0, 0), food: 0, wrap: false, diagonal: false, hazards: vec![],
+        }
+    }
+
+    pub fn filled(width: i32, height: i32, tile: Tile) -> Map {
+        Map { data: vec![tile; (width * height) as usize], ..Map::new(width, height) }
     }
 
     pub fn get(&self, y: i32, x: i32) -> Option<Tile> {
@@ -56,69 +139,381 @@ impl Map {
         self.data[(y * self.width + x) as usize] = tile;
     }
 
-    pub fn print(&self) {
-        self.print_with_path(vec![]);
+    // Where the head ends up moving from (y, x) by (dy, dx) with the portal it
+    // went through if any, or None if the move is not allowed. The portal cell
+    // is entered too, so it has to be free as well as the landing cell.
+    pub fn step(&self, y: i32, x: i32, dy: i32, dx: i32) -> Option<((i32, i32), Option<(i32, i32)>)> {
+        let (ny, nx) = if self.wrap {
+            ((y + dy + self.height) % self.height, (x + dx + self.width) % self.width)
+        } else {
+            (y + dy, x + dx)
+        };
+        match self.get(ny, nx) {
+            None | Some(Tile::Pit) | Some(Tile::Wall) | Some(Tile::Head(_)) => None,
+            Some(Tile::OneWay(ay, ax)) if (ay, ax) != (dy, dx) => None,
+            Some(Tile::Portal(_)) => self.portals.get(&(ny, nx)).map(|&to| (to, Some((ny, nx)))),
+            _ => Some(((ny, nx), None)),
+        }
+    }
+
+    // How many moves it takes to enter the cell.
+    pub fn cost(&self, y: i32, x: i32) -> i32 {
+        if self.get(y, x) == Some(Tile::Mud) { MUD_COST } else { 1 }
+    }
+
+    pub fn path_cost(&self, path: &[(char, i32, i32)]) -> i32 {
+        path.iter().fold(0, |acc, &(_, y, x)| acc + self.cost(y, x))
+    }
+
+    // Whether the cell is a pit at that time.
+    pub fn pit_at(&self, y: i32, x: i32, time: i32) -> bool {
+        self.hazards.iter().any(|&h| match h {
+            Hazard::Pit(cell, period, open) => cell == (y, x) && time % period < open,
+            _ => false,
+        })
     }
 
-    pub fn print_with_path(&self, path: Vec<(char, i32, i32)>) {
-        let path_set: HashMap<(i32, i32), char> = path.iter().map(|&(c, y, x)| ((y, x), c)).collect();
-        let print_border = || {
-            print!("+");
-            for _ in 0..self.width { print!("-"); }
-            println!("+");
+    // The last time the food on the cell can be eaten, if it expires.
+    pub fn deadline(&self, y: i32, x: i32) -> Option<i32> {
+        self.hazards.iter().filter_map(|&h| match h {
+            Hazard::Expires(cell, moves) if cell == (y, x) => Some(moves),
+            _ => None,
+        }).next()
+    }
+
+    // The map as it looks at that time: open pits and expired food.
+    pub fn at_time(&self, time: i32) -> Map {
+        let mut map = self.clone();
+        for &h in &self.hazards {
+            match h {
+                Hazard::Pit((y, x), _, _) => {
+                    if self.get(y, x) == Some(Tile::Empty) && self.pit_at(y, x, time) {
+                        map.set(y, x, Tile::Pit);
+                    }
+                },
+                Hazard::Expires((y, x), moves) => {
+                    if self.get(y, x) == Some(Tile::Food) && time > moves {
+                        map.set(y, x, Tile::Empty);
+                        map.food -= 1;
+                    }
+                },
+            }
+        }
+        map
+    }
+
+    // The heads of all the snakes in the order of SNAKES, 's' first.
+    pub fn heads(&self) -> Vec<(char, (i32, i32))> {
+        let mut heads = vec![('s', self.head)];
+        for &c in &SNAKES[1..] {
+            if let Some(i) = self.data.iter().position(|&t| t == Tile::Head(c)) {
+                heads.push((c, (i as i32 / self.width, i as i32 % self.width)));
+            }
+        }
+        heads
+    }
+
+    // The title followed by the options, like "map 1 [wrap diagonal]".
+    fn full_title(&self) -> String {
+        let mut options = Vec::new();
+        if self.wrap {
+            options.push("wrap");
+        }
+        if self.diagonal {
+            options.push("diagonal");
+        }
+        match (self.title.is_empty(), options.is_empty()) {
+            (_, true) => self.title.clone(),
+            (true, false) => format!("[{}]", options.join(" ")),
+            (false, false) => format!("{} [{}]", self.title, options.join(" ")),
+        }
+    }
+
+    // The top border with the title between dashes, like "+- map 1 --+".
+    // Titles that could not be read back are an error: too long for the
+    // map, starting or ending with the dashes and spaces around them, or
+    // ending like options.
+    fn top_border(&self) -> io::Result<String> {
+        let width = self.width as usize;
+        let t = &self.title;
+        if t.trim_matches(|c| c == '-' || c == ' ') != t || t.ends_with(']') || t.chars().any(|c| c.is_control()) {
+            return Err(io::Error::new(io::ErrorKind::InvalidInput,
+                                      format!("title \"{}\" cannot be read back", t)));
+        }
+        let title = self.full_title();
+        let len = title.chars().count();
+        if len > width {
+            return Err(io::Error::new(io::ErrorKind::InvalidInput,
+                                      format!("title \"{}\" does not fit in a map {} wide", title, width)));
+        }
+        let inner = if len == 0 {
+            String::new()
+        } else if len + 2 <= width {
+            let left = (width - len - 2) / 2;
+            format!("{} {} ", dashes(left), title)
+        } else {
+            title
         };
-        print_border();
+        Ok(format!("+{}{}+", inner, dashes(width - inner.chars().count())))
+    }
+
+    // Write the map in the same bordered format that parse_maps reads.
+    pub fn write<W: Write>(&self, out: &mut W) -> io::Result<()> {
+        try!(writeln!(out, "{}", try!(self.top_border())));
         for y in 0..self.height {
-            print!("|");
-            for x in 0..self.width {
-                let c = match self.get(y, x).unwrap() {
-                    Tile::Empty => ' ',
-                    Tile::Snake => 's',
-                    Tile::Food => '*',
-                    Tile::Pit => 'O',
-                };
-                let x = path_set.get(&(y, x)).unwrap_or(&c);
-                print!("{}", x);
+            let row: String = (0..self.width).map(|x| self.get(y, x).unwrap().to_char()).collect();
+            try!(writeln!(out, "|{}|", row));
+        }
+        try!(writeln!(out, "+{}+", dashes(self.width as usize)));
+        for &h in &self.hazards {
+            match h {
+                Hazard::Pit((y, x), period, open) => try!(writeln!(out, "pit {} {} {} {}", y, x, period, open)),
+                Hazard::Expires((y, x), moves) => try!(writeln!(out, "food {} {} {}", y, x, moves)),
             }
-            println!("|");
         }
-        print_border();
+        Ok(())
     }
 }
 
-fn parse_maps(lines: Vec<String>) -> Result<Vec<Map>, ParseError> {
-    let mut maps = Vec::new();
-    let mut map = Map::new(0, 0);
+fn dashes(n: usize) -> String {
+    iter::repeat('-').take(n).collect()
+}
 
-    for line in lines {
-        let chars: Vec<char> = line.chars().collect();
-        if chars.len() < 1 {
-            continue
+pub fn write_maps<W: Write>(out: &mut W, maps: &[Map]) -> io::Result<()> {
+    for (n, map) in maps.iter().enumerate() {
+        if n > 0 {
+            try!(writeln!(out, ""));
         }
-        if chars[0] == '+' {
-            if line.as_str().contains("map") {
-                map = Map::new((chars.len() - 2) as i32, 0);
-            } else {
-                let h = map.data.iter().position(|&t| t == Tile::Snake).unwrap_or(0) as i32;
-                map.head = (h / map.width, h % map.width);
-                map.food = map.data.iter().filter(|&t| *t == Tile::Food).count() as i32;
-                maps.push(map.clone());
+        try!(map.write(out));
+    }
+    Ok(())
+}
+
+fn format_error<T>(line: usize, col: usize, msg: String) -> Result<T, ParseError> {
+    Err(ParseError::Format(line + 1, col + 1, msg))
+}
+
+// The top border holds the map title between dashes, e.g. "+- map 1 --+",
+// optionally ending with options in brackets: "+- map 1 [wrap diagonal] -+".
+fn parse_top(n: usize, chars: &[char]) -> Result<Map, ParseError> {
+    if chars.len() < 3 || chars[chars.len() - 1] != '+' {
+        return format_error(n, chars.len(), "the border should end with '+'".to_string());
+    }
+    let mut map = Map::new((chars.len() - 2) as i32, 0);
+    let end = chars.len() - 1 - chars[1..(chars.len() - 1)].iter().rev().take_while(|&&c| c == '-' || c == ' ').count();
+    let mut title_end = end;
+    if end > 1 && chars[end - 1] == ']' {
+        let open = match chars[1..end].iter().rposition(|&c| c == '[') {
+            Some(p) => p + 1,
+            None => return format_error(n, end - 1, "unmatched ']' in the title".to_string()),
+        };
+        let mut col = open + 1;
+        for word in chars[(open + 1)..(end - 1)].split(|&c| c == ' ') {
+            let option: String = word.iter().cloned().collect();
+            match option.as_str() {
+                "" => {},
+                "wrap" => map.wrap = true,
+                "diagonal" => map.diagonal = true,
+                _ => return format_error(n, col, format!("unknown map option '{}'", option)),
             }
-            continue;
+            col += word.len() + 1;
         }
-        for c in chars {
-            match c {
-                '|' => continue,
-                ' ' => map.data.push(Tile::Empty),
-                's' => map.data.push(Tile::Snake),
-                '*' => map.data.push(Tile::Food),
-                'O' => map.data.push(Tile::Pit),
-                x   => return Err(ParseError::Format(format!("Unknown char {}", x))),
-            };
+        title_end = open;
+    }
+    let title: String = chars[1..title_end].iter().cloned().collect();
+    map.title = title.trim_matches('-').trim().to_string();
+    Ok(map)
+}
+
+fn parse_bottom(n: usize, chars: &[char], map: &Map) -> Result<(), ParseError> {
+    if chars.len() != (map.width + 2) as usize {
+        return format_error(n, 0, format!("bottom border is {} wide, expected {}", chars.len(), map.width + 2));
+    }
+    if let Some(col) = chars[1..(chars.len() - 1)].iter().position(|&c| c != '-') {
+        return format_error(n, col + 1, format!("unexpected '{}' in the bottom border", chars[col + 1]));
+    }
+    if chars[chars.len() - 1] != '+' {
+        return format_error(n, chars.len() - 1, "the border should end with '+'".to_string());
+    }
+    Ok(())
+}
+
+fn parse_row(n: usize, chars: &[char], map: &mut Map, head: &mut Option<(usize, usize)>) -> Result<(), ParseError> {
+    let width = map.width as usize;
+    if chars[0] != '|' {
+        return format_error(n, 0, format!("expected '|', found '{}'", chars[0]));
+    }
+    if chars.len() < width + 2 && chars[chars.len() - 1] == '|' && chars.len() > 1 {
+        return format_error(n, chars.len() - 1, format!("row is {} wide, expected {}", chars.len() - 2, width));
+    }
+    if chars.len() < width + 2 {
+        return format_error(n, chars.len(), "row should end with '|'".to_string());
+    }
+    if chars.len() > width + 2 || chars[width + 1] != '|' {
+        return format_error(n, width + 1, format!("row is wider than {}", width));
+    }
+    for (col, &c) in chars.iter().enumerate().skip(1).take(width) {
+        let tile = match Tile::from_char(c) {
+            Some(tile) => tile,
+            None => return format_error(n, col, format!("unknown char '{}'", c)),
+        };
+        if tile == Tile::Snake {
+            if let Some((l, c)) = *head {
+                return format_error(n, col, format!("second head, the first one is at line {}, column {}", l + 1, c + 1));
+            }
+            *head = Some((n, col));
+            map.head = (map.height, col as i32 - 1);
         }
-        map.height += 1;
+        map.data.push(tile);
     }
+    map.height += 1;
+    Ok(())
+}
 
+// Every snake has a single head.
+fn check_heads(map: &Map, top: usize) -> Result<(), ParseError> {
+    let mut seen = Vec::new();
+    for (i, &tile) in map.data.iter().enumerate() {
+        if let Tile::Head(c) = tile {
+            let (y, x) = (i / map.width as usize, i % map.width as usize);
+            if let Some(&(_, fy, fx)) = seen.iter().find(|&&(s, _, _)| s == c) {
+                return format_error(top + 1 + y, x + 1, format!("second head '{}', the first one is at line {}, column {}",
+                                                               c, top + 2 + fy, fx + 2));
+            }
+            seen.push((c, y, x));
+        }
+    }
+    Ok(())
+}
+
+// Every portal digit has to appear exactly twice in a map.
+fn link_portals(map: &mut Map, top: usize) -> Result<(), ParseError> {
+    let mut cells: BTreeMap<char, Vec<(i32, i32)>> = BTreeMap::new();
+    for y in 0..map.height {
+        for x in 0..map.width {
+            if let Some(Tile::Portal(c)) = map.get(y, x) {
+                cells.entry(c).or_insert_with(Vec::new).push((y, x));
+            }
+        }
+    }
+    for (c, pos) in cells {
+        if pos.len() != 2 {
+            let (y, x) = pos[0];
+            return format_error(top + 1 + y as usize, x as usize + 1,
+                                format!("portal '{}' appears {} times, expected 2", c, pos.len()));
+        }
+        map.portals.insert(pos[0], pos[1]);
+        map.portals.insert(pos[1], pos[0]);
+    }
+    Ok(())
+}
+
+// One line of the annotation block after a map, see Hazard.
+fn parse_hazard(n: usize, line: &str, map: &mut Map) -> Result<(), ParseError> {
+    let mut words = Vec::new();
+    let mut col = 0;
+    for word in line.split(' ') {
+        if !word.is_empty() {
+            words.push((col, word));
+        }
+        col += word.chars().count() + 1;
+    }
+    let (kind, count) = match words[0].1 {
+        "pit" => ("pit", 5),
+        "food" => ("food", 4),
+        w => return format_error(n, words[0].0, format!("unknown annotation '{}', expected 'pit' or 'food'", w)),
+    };
+    if words.len() != count {
+        return format_error(n, 0, format!("'{}' takes {} numbers", kind, count - 1));
+    }
+    let mut nums = Vec::new();
+    for &(col, word) in &words[1..] {
+        match word.parse::<i32>() {
+            Ok(v) if v >= 0 => nums.push(v),
+            _ => return format_error(n, col, format!("expected a number, found '{}'", word)),
+        }
+    }
+    let cell = (nums[0], nums[1]);
+    let tile = match map.get(cell.0, cell.1) {
+        Some(tile) => tile,
+        None => return format_error(n, words[1].0, format!("({}, {}) is outside of the map", cell.0, cell.1)),
+    };
+    if kind == "pit" {
+        if tile != Tile::Empty {
+            return format_error(n, words[1].0, format!("pit on '{}', expected an empty cell", tile.to_char()));
+        }
+        if nums[2] == 0 || nums[3] > nums[2] {
+            return format_error(n, words[3].0, "expected 0 < period and open <= period".to_string());
+        }
+        map.hazards.push(Hazard::Pit(cell, nums[2], nums[3]));
+    } else {
+        if tile != Tile::Food {
+            return format_error(n, words[1].0, format!("expiring food on '{}'", tile.to_char()));
+        }
+        map.hazards.push(Hazard::Expires(cell, nums[2]));
+    }
+    Ok(())
+}
+
+pub fn parse_maps(lines: Vec<String>) -> Result<Vec<Map>, ParseError> {
+    let mut maps: Vec<Map> = Vec::new();
+    // Whether the lines right after the last map are still its annotations.
+    let mut annotating = false;
+    // The map being parsed, with the line of its top border and its head.
+    let mut current: Option<(Map, usize, Option<(usize, usize)>)> = None;
+
+    for (n, line) in lines.iter().enumerate() {
+        // Rows keep their trailing spaces, which are empty cells.
+        let chars: Vec<char> = line.trim_right_matches('\r').chars().collect();
+        let border: Vec<char> = line.trim_right().chars().collect();
+        let done = match current {
+            None => {
+                if line.trim().is_empty() {
+                    annotating = false;
+                    continue;
+                }
+                if annotating && border[0] != '+' {
+                    try!(parse_hazard(n, line.trim_right(), maps.last_mut().unwrap()));
+                    continue;
+                }
+                annotating = false;
+                if border[0] != '+' {
+                    return format_error(n, 0, "expected the top border of a map".to_string());
+                }
+                current = Some((try!(parse_top(n, &border)), n, None));
+                false
+            },
+            Some((ref mut map, top, ref mut head)) => {
+                if border.is_empty() {
+                    return format_error(n, 0, "empty line inside a map".to_string());
+                }
+                if chars[0] != '+' {
+                    try!(parse_row(n, &chars, map, head));
+                    false
+                } else {
+                    try!(parse_bottom(n, &border, map));
+                    if map.height == 0 {
+                        return format_error(top, 0, "map without rows".to_string());
+                    }
+                    if head.is_none() {
+                        return format_error(top, 0, "map without a head 's'".to_string());
+                    }
+                    try!(check_heads(map, top));
+                    try!(link_portals(map, top));
+                    map.food = map.data.iter().filter(|&t| *t == Tile::Food).count() as i32;
+                    maps.push(map.clone());
+                    annotating = true;
+                    true
+                }
+            },
+        };
+        if done {
+            current = None;
+        }
+    }
+    if let Some((_, top, _)) = current {
+        return format_error(top, 0, "map without a bottom border".to_string());
+    }
     Ok(maps)
 }
 
@@ -137,3 +532,169 @@ pub fn parse_input_file(path: &str) -> Result<Vec<Map>, ParseError> {
     let lines = try!(get_lines(path));
     parse_maps(lines)
 }
+
+// Link two cells as the portals of digit `c`, for tests.
+#[cfg(test)]
+impl Map {
+    pub fn add_portal(&mut self, c: char, a: (i32, i32), b: (i32, i32)) {
+        self.set(a.0, a.1, Tile::Portal(c));
+        self.set(b.0, b.1, Tile::Portal(c));
+        self.portals.insert(a, b);
+        self.portals.insert(b, a);
+    }
+}
+
+// The only map in the text, for tests.
+#[cfg(test)]
+pub fn from_text(text: &str) -> Map {
+    let mut maps = parse_maps(text.lines().map(|l| l.to_string()).collect()).unwrap();
+    assert_eq!(maps.len(), 1);
+    maps.pop().unwrap()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(text: &str) -> Result<Vec<Map>, ParseError> {
+        parse_maps(text.lines().map(|l| l.to_string()).collect())
+    }
+
+    // The line, column and message of the format error.
+    fn error(text: &str) -> (usize, usize, String) {
+        match parse(text) {
+            Err(ParseError::Format(line, col, msg)) => (line, col, msg),
+            Err(ParseError::Io(e)) => panic!("unexpected io error {}", e),
+            Ok(_) => panic!("parsed {:?}", text),
+        }
+    }
+
+    fn err(line: usize, col: usize, msg: &str) -> (usize, usize, String) {
+        (line, col, msg.to_string())
+    }
+
+    #[test]
+    fn row_narrower_than_the_border() {
+        assert_eq!(error("+-----+\n|s    |\n|   |\n+-----+"), err(3, 5, "row is 3 wide, expected 5"));
+    }
+
+    #[test]
+    fn row_wider_than_the_border() {
+        assert_eq!(error("+-----+\n|s     |\n+-----+"), err(2, 7, "row is wider than 5"));
+    }
+
+    #[test]
+    fn row_without_closing_bar() {
+        assert_eq!(error("+-----+\n|s    \n+-----+"), err(2, 7, "row should end with '|'"));
+    }
+
+    #[test]
+    fn unknown_char() {
+        assert_eq!(error("+-----+\n|s q  |\n+-----+"), err(2, 4, "unknown char 'q'"));
+    }
+
+    #[test]
+    fn no_head() {
+        assert_eq!(error("\n+-----+\n|  *  |\n+-----+"), err(2, 1, "map without a head 's'"));
+    }
+
+    #[test]
+    fn second_head() {
+        assert_eq!(error("+-----+\n|s    |\n|  s  |\n+-----+"),
+                   err(3, 4, "second head, the first one is at line 2, column 2"));
+    }
+
+    #[test]
+    fn second_head_of_another_snake() {
+        assert_eq!(error("+-----+\n|st   |\n|  t  |\n+-----+"),
+                   err(3, 4, "second head 't', the first one is at line 2, column 3"));
+    }
+
+    #[test]
+    fn unpaired_portal() {
+        assert_eq!(error("+-----+\n|s 1 2|\n|  1  |\n+-----+"), err(2, 6, "portal '2' appears 1 times, expected 2"));
+    }
+
+    #[test]
+    fn no_bottom_border() {
+        assert_eq!(error("+-----+\n|s   *|"), err(1, 1, "map without a bottom border"));
+    }
+
+    #[test]
+    fn bad_bottom_border() {
+        assert_eq!(error("+-----+\n|s   *|\n+--x--+"), err(3, 4, "unexpected 'x' in the bottom border"));
+        assert_eq!(error("+-----+\n|s   *|\n+---+"), err(3, 1, "bottom border is 5 wide, expected 7"));
+    }
+
+    #[test]
+    fn bad_top_border() {
+        assert_eq!(error("+-----\n|s   *|\n+-----+"), err(1, 7, "the border should end with '+'"));
+        assert_eq!(error("+ m [fly] +\n|s   *    |\n+---------+"), err(1, 6, "unknown map option 'fly'"));
+    }
+
+    #[test]
+    fn empty_line_inside_a_map() {
+        assert_eq!(error("+-----+\n|s   *|\n\n+-----+"), err(3, 1, "empty line inside a map"));
+    }
+
+    #[test]
+    fn title_is_kept() {
+        let maps = parse("+- map 1 -+\n|s   *    |\n+---------+\n\n+-------+\n|s  *   |\n+-------+").unwrap();
+        assert_eq!(maps[0].title, "map 1");
+        assert_eq!(maps[1].title, "");
+    }
+
+    fn round_trip(map: &Map) -> Map {
+        let mut out = Vec::new();
+        map.write(&mut out).unwrap();
+        let text = String::from_utf8(out).unwrap();
+        let mut maps = parse(&text).unwrap();
+        assert_eq!(maps.len(), 1);
+        maps.pop().unwrap()
+    }
+
+    #[test]
+    fn written_maps_read_back_the_same() {
+        let text = "+- all [wrap diagonal] -+\n\
+                    |s 1 # ~ ^ v < > O * t 1|\n\
+                    |  *         u          |\n\
+                    +-----------------------+\n\
+                    pit 1 0 3 1\n\
+                    food 1 2 7";
+        let map = parse(text).unwrap().pop().unwrap();
+        assert_eq!(round_trip(&map), map);
+        let mut untitled = map.clone();
+        untitled.title = String::new();
+        untitled.wrap = false;
+        assert_eq!(round_trip(&untitled), untitled);
+    }
+
+    #[test]
+    fn titles_that_do_not_fit_are_rejected() {
+        let mut map = parse("+---+\n|s *|\n+---+").unwrap().pop().unwrap();
+        map.title = "abc".to_string();
+        assert_eq!(round_trip(&map), map);
+        map.title = "map 1".to_string();
+        assert!(map.write(&mut Vec::new()).is_err());
+    }
+
+    #[test]
+    fn titles_that_read_back_differently_are_rejected() {
+        let mut map = parse("+------+\n|s    *|\n+------+").unwrap().pop().unwrap();
+        for title in &["x-", "-x", " x", "x ", "a]", "a [b]", "a\tb"] {
+            map.title = title.to_string();
+            assert!(map.write(&mut Vec::new()).is_err(), "{:?}", title);
+        }
+        for title in &["a-b", "a b", "[a", "a]b"] {
+            map.title = title.to_string();
+            assert_eq!(round_trip(&map), map);
+        }
+    }
+
+    #[test]
+    fn options_are_read_from_the_title() {
+        let maps = parse("+- maze [wrap diagonal] -+\n|s        *              |\n+------------------------+").unwrap();
+        assert_eq!(maps[0].title, "maze");
+        assert!(maps[0].wrap && maps[0].diagonal);
+    }
+}
@@ -0,0 +1,177 @@
+use bfs::FoodDistances;
+use lib::astar::{Graph, a_star_until};
+use map::{MAX_SNAKES, Map};
+use render::{Theme, write_path};
+use search::{all_eaten, eat, food_cells, moves};
+use std::cmp;
+use std::io;
+use std::rc::Rc;
+
+// Several snakes move at the same time, one round after the other. Like the
+// single snake, none of them ever leaves a cell it visited, so two snakes
+// can never enter the same cell.
+#[derive(Clone, PartialEq, Eq, Hash)]
+pub struct Herd {
+    pub heads: Vec<(i32, i32)>,
+    pub eaten: Rc<Vec<bool>>,
+    pub visited: Rc<Vec<bool>>,
+}
+
+// What every snake does in a round: a move, or None to stay where it is.
+// Only the first entries are used, one per snake.
+pub type Round = [Option<(char, i32, i32)>; MAX_SNAKES];
+
+pub struct HerdGraph<'a> {
+    pub map: &'a Map,
+    food: Vec<(i32, i32)>,
+    food_index: Vec<Option<usize>>,
+    dist: FoodDistances,
+}
+
+impl<'a> HerdGraph<'a> {
+    pub fn new(map: &'a Map) -> HerdGraph<'a> {
+        let (food, food_index) = food_cells(map);
+        let dist = FoodDistances::new(map, &food);
+        HerdGraph { map: map, food: food, food_index: food_index, dist: dist }
+    }
+
+    fn index(&self, (y, x): (i32, i32)) -> usize {
+        (y * self.map.width + x) as usize
+    }
+
+    pub fn start(&self) -> Herd {
+        let heads: Vec<(i32, i32)> = self.map.heads().iter().map(|&(_, h)| h).collect();
+        let mut visited = vec![false; (self.map.width * self.map.height) as usize];
+        for &h in &heads {
+            visited[self.index(h)] = true;
+        }
+        Herd { heads: heads, eaten: Rc::new(vec![false; self.food.len()]), visited: Rc::new(visited) }
+    }
+
+    // The moves of one snake to cells nobody visited yet, with the portal
+    // they go through if any.
+    fn options(&self, node: &Herd, head: (i32, i32)) -> Vec<Option<((char, i32, i32), Option<(i32, i32)>)>> {
+        let mut res = vec![None];
+        for &(m, dy, dx) in moves(self.map) {
+            if let Some(((y, x), portal)) = self.map.step(head.0, head.1, dy, dx) {
+                let free = |cell| !node.visited[self.index(cell)];
+                if free((y, x)) && portal.map_or(true, &free) {
+                    res.push(Some(((m, y, x), portal)));
+                }
+            }
+        }
+        res
+    }
+}
+
+impl<'a> Graph for HerdGraph<'a> {
+    type Node = Herd;
+    type Move = Round;
+
+    fn is_goal(&self, node: &Herd) -> bool {
+        all_eaten(&node.eaten)
+    }
+
+    fn neighbors(&self, node: &Herd) -> Vec<(Round, Herd)> {
+        // Every combination of the snakes' options, without two snakes
+        // entering the same cell, portals included.
+        let mut rounds: Vec<(Round, Vec<(i32, i32)>)> = vec![([None; MAX_SNAKES], vec![])];
+        for (i, &head) in node.heads.iter().enumerate() {
+            let options = self.options(node, head);
+            let mut next = Vec::new();
+            for &(ref round, ref entered) in &rounds {
+                for &opt in &options {
+                    let cells: Vec<(i32, i32)> = match opt {
+                        Some(((_, y, x), portal)) => Some((y, x)).into_iter().chain(portal).collect(),
+                        None => vec![],
+                    };
+                    if cells.iter().any(|c| entered.contains(c)) {
+                        continue;
+                    }
+                    let mut round = *round;
+                    round[i] = opt.map(|(m, _)| m);
+                    let mut entered = entered.clone();
+                    entered.extend(cells);
+                    next.push((round, entered));
+                }
+            }
+            rounds = next;
+        }
+
+        let mut res = Vec::new();
+        for (round, _) in rounds {
+            if round.iter().all(|o| o.is_none()) {
+                continue;
+            }
+            let mut visited = node.visited.as_ref().clone();
+            let mut heads = node.heads.clone();
+            let mut eaten = node.eaten.clone();
+            for (i, &opt) in round.iter().enumerate() {
+                if let Some((_, y, x)) = opt {
+                    let cell = self.index((y, x));
+                    visited[cell] = true;
+                    heads[i] = (y, x);
+                    if let Some(f) = self.food_index[cell] {
+                        eaten = eat(&eaten, f);
+                    }
+                }
+            }
+            res.push((round, Herd { heads: heads, eaten: eaten, visited: Rc::new(visited) }));
+        }
+        res
+    }
+}
+
+// A round lasts as long as its slowest move.
+pub fn round_cost(map: &Map, round: &Round) -> i32 {
+    round.iter().filter_map(|o| o.map(|(_, y, x)| map.cost(y, x))).max().unwrap_or(1)
+}
+
+// Every remaining food has to be reached by the closest snake.
+pub fn h_herd(g: &HerdGraph, node: &Herd) -> i32 {
+    (0..g.food.len())
+        .filter(|&f| !node.eaten[f])
+        .map(|f| node.heads.iter().map(|&h| g.dist.cell(h, f)).min().unwrap())
+        .fold(0, cmp::max)
+}
+
+pub fn plan(map: &Map) -> Option<Vec<Round>> {
+    plan_until(map, || false).unwrap()
+}
+
+// Gives up when stop() returns true, with the cost no plan is below.
+pub fn plan_until<S: Fn() -> bool>(map: &Map, stop: S) -> Result<Option<Vec<Round>>, i32> {
+    let graph = HerdGraph::new(map);
+    let (_, res) = a_star_until(&graph, graph.start(), h_herd, |g, _, r| round_cost(g.map, r), stop);
+    res.map(|path| path.map(|p| p.into_iter().map(|(r, _)| r).collect()))
+}
+
+// The cells entered by every snake, marked with the upper case letter of the
+// snake, round after round.
+pub fn trails(names: &[char], rounds: &[Round]) -> Vec<(char, i32, i32)> {
+    let mut res = Vec::new();
+    for round in rounds {
+        for (&c, &opt) in names.iter().zip(round.iter()) {
+            if let Some((_, y, x)) = opt {
+                res.push((c.to_uppercase().next().unwrap(), y, x));
+            }
+        }
+    }
+    res
+}
+
+// The moves of every snake, in the order of Map::heads.
+pub fn paths(map: &Map, rounds: &[Round]) -> Vec<Vec<(char, i32, i32)>> {
+    (0..map.heads().len()).map(|i| rounds.iter().filter_map(|r| r[i]).collect()).collect()
+}
+
+pub fn print_plan(map: &Map, rounds: &[Round], theme: &Theme) {
+    let cost = rounds.iter().fold(0, |acc, r| acc + round_cost(map, r));
+    println!("{} rounds, cost {}", rounds.len(), cost);
+    let names: Vec<char> = map.heads().iter().map(|&(c, _)| c).collect();
+    for (i, &c) in names.iter().enumerate() {
+        let moves: String = rounds.iter().map(|r| r[i].map_or('·', |(m, _, _)| m)).collect();
+        println!("{}: {}", c, moves);
+    }
+    write_path(&mut io::stdout(), map, &trails(&names, rounds), theme).unwrap();
+}
@@ -0,0 +1,157 @@
+use map::{Map, Tile};
+use std::collections::HashMap;
+use std::io;
+use std::io::prelude::*;
+use std::iter;
+
+const RED: &'static str = "\x1b[31m";
+const GREEN: &'static str = "\x1b[1;32m";
+const YELLOW: &'static str = "\x1b[33m";
+const BLUE: &'static str = "\x1b[34m";
+const MAGENTA: &'static str = "\x1b[35m";
+const GREY: &'static str = "\x1b[90m";
+const BOLD: &'static str = "\x1b[1m";
+const RESET: &'static str = "\x1b[0m";
+
+// Drawn as a move on food the head cannot reach.
+pub const UNREACHABLE: char = '!';
+
+// How cells are drawn. Every cell takes `width` visible characters.
+pub trait Theme {
+    fn width(&self) -> usize {
+        1
+    }
+
+    fn tile(&self, tile: Tile) -> String {
+        tile.to_char().to_string()
+    }
+
+    // A cell entered by the move `m`, the `step`-th of the path (from 1).
+    fn path(&self, tile: Tile, m: char, step: usize) -> String;
+}
+
+// The arrows of the moves, as printed by default.
+pub struct Unicode;
+
+impl Theme for Unicode {
+    fn path(&self, _: Tile, m: char, _: usize) -> String {
+        m.to_string()
+    }
+}
+
+// Arrows for terminals that mangle Unicode. The arrows are the characters
+// of one-way tiles in map files, so one-way tiles get other ones.
+pub struct Ascii;
+
+impl Theme for Ascii {
+    fn tile(&self, tile: Tile) -> String {
+        match tile {
+            Tile::OneWay(-1, _) => "A",
+            Tile::OneWay(1, _) => "V",
+            Tile::OneWay(_, -1) => "{",
+            Tile::OneWay(_, _) => "}",
+            _ => return tile.to_char().to_string(),
+        }.to_string()
+    }
+
+    fn path(&self, _: Tile, m: char, _: usize) -> String {
+        match m {
+            '↑' => "^",
+            '↓' => "v",
+            '←' => "<",
+            '→' => ">",
+            '↖' | '↘' => "\\",
+            '↗' | '↙' => "/",
+            _ => return m.to_string(),
+        }.to_string()
+    }
+}
+
+pub struct Ansi;
+
+impl Theme for Ansi {
+    fn tile(&self, tile: Tile) -> String {
+        let colour = match tile {
+            Tile::Pit => RED,
+            Tile::Food => YELLOW,
+            Tile::Snake | Tile::Head(_) => BOLD,
+            Tile::Wall => GREY,
+            Tile::Portal(_) => MAGENTA,
+            Tile::OneWay(_, _) | Tile::Mud => BLUE,
+            Tile::Empty => return " ".to_string(),
+        };
+        format!("{}{}{}", colour, tile.to_char(), RESET)
+    }
+
+    fn path(&self, _: Tile, m: char, _: usize) -> String {
+        let colour = if m == UNREACHABLE { RED } else { GREEN };
+        format!("{}{}{}", colour, m, RESET)
+    }
+}
+
+// The index of the move on every cell of the path.
+pub struct Steps;
+
+impl Theme for Steps {
+    fn width(&self) -> usize {
+        3
+    }
+
+    fn tile(&self, tile: Tile) -> String {
+        format!("{:>3}", tile.to_char())
+    }
+
+    fn path(&self, _: Tile, m: char, step: usize) -> String {
+        if m == UNREACHABLE {
+            format!("{:>3}", m)
+        } else {
+            format!("{:>3}", step)
+        }
+    }
+}
+
+pub fn theme(name: &str) -> Option<Box<Theme>> {
+    match name {
+        "unicode" => Some(Box::new(Unicode)),
+        "ascii" => Some(Box::new(Ascii)),
+        "color" => Some(Box::new(Ansi)),
+        "steps" => Some(Box::new(Steps)),
+        _ => None,
+    }
+}
+
+// The map with the path drawn over it. A cell visited twice shows its last
+// visit.
+pub fn write_path<W: Write>(out: &mut W, map: &Map, path: &[(char, i32, i32)], theme: &Theme) -> io::Result<()> {
+    let on_path: HashMap<(i32, i32), (char, usize)> = path.iter().enumerate()
+        .map(|(i, &(m, y, x))| ((y, x), (m, i + 1)))
+        .collect();
+    let border: String = iter::repeat('-').take(map.width as usize * theme.width()).collect();
+    try!(writeln!(out, "+{}+", border));
+    for y in 0..map.height {
+        let mut row = String::new();
+        for x in 0..map.width {
+            let tile = map.get(y, x).unwrap();
+            row.push_str(&match on_path.get(&(y, x)) {
+                Some(&(m, step)) => theme.path(tile, m, step),
+                None => theme.tile(tile),
+            });
+        }
+        try!(writeln!(out, "|{}|", row));
+    }
+    writeln!(out, "+{}+", border)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use map::from_text;
+
+    #[test]
+    fn ascii_arrows_and_one_way_tiles_differ() {
+        let map = from_text("+-----+\n|s<v*>|\n+-----+");
+        let mut out = Vec::new();
+        write_path(&mut out, &map, &[('↓', 0, 0)], &Ascii).unwrap();
+        assert_eq!(String::from_utf8(out).unwrap(), "+-----+\n|v{V*}|\n+-----+\n");
+    }
+}
@@ -0,0 +1,199 @@
+use bfs::FoodDistances;
+use lib::astar::{Graph, a_star_until};
+use map::{Map, Tile};
+use std::rc::Rc;
+
+pub const MOVES: [(char, i32, i32); 4] = [('↑', -1, 0), ('↓', 1, 0), ('←', 0, -1), ('→', 0, 1)];
+pub const DIAGONAL_MOVES: [(char, i32, i32); 8] = [
+    ('↑', -1, 0), ('↓', 1, 0), ('←', 0, -1), ('→', 0, 1),
+    ('↖', -1, -1), ('↗', -1, 1), ('↙', 1, -1), ('↘', 1, 1),
+];
+
+// The moves allowed on the map.
+pub fn moves(map: &Map) -> &'static [(char, i32, i32)] {
+    if map.diagonal { &DIAGONAL_MOVES } else { &MOVES }
+}
+
+// The snake can never go back to a cell it visited, so a state is the head
+// position, the food eaten so far (one flag per food) and the visited cells.
+// On maps with hazards the time matters too, otherwise it stays 0.
+#[derive(Clone, PartialEq, Eq, Hash)]
+pub struct State {
+    pub head: (i32, i32),
+    pub eaten: Rc<Vec<bool>>,
+    pub visited: Rc<Vec<bool>>,
+    pub time: i32,
+}
+
+pub struct SnakeGraph<'a> {
+    pub map: &'a Map,
+    pub food: Vec<(i32, i32)>,
+    pub food_index: Vec<Option<usize>>,
+    pub dist: FoodDistances,
+    deadlines: Vec<Option<i32>>,
+}
+
+impl<'a> SnakeGraph<'a> {
+    pub fn new(map: &'a Map) -> SnakeGraph<'a> {
+        let (food, food_index) = food_cells(map);
+        let dist = FoodDistances::new(map, &food);
+        let deadlines = food.iter().map(|&(y, x)| map.deadline(y, x)).collect();
+        SnakeGraph { map: map, food: food, food_index: food_index, dist: dist, deadlines: deadlines }
+    }
+
+    fn index(&self, y: i32, x: i32) -> usize {
+        (y * self.map.width + x) as usize
+    }
+
+    pub fn start(&self) -> State {
+        let mut visited = vec![false; (self.map.width * self.map.height) as usize];
+        visited[self.index(self.map.head.0, self.map.head.1)] = true;
+        State { head: self.map.head, eaten: Rc::new(vec![false; self.food.len()]), visited: Rc::new(visited), time: 0 }
+    }
+}
+
+// The food positions, and for every cell the index of its food if any.
+pub fn food_cells(map: &Map) -> (Vec<(i32, i32)>, Vec<Option<usize>>) {
+    let mut food = Vec::new();
+    let mut food_index = Vec::new();
+    for y in 0..map.height {
+        for x in 0..map.width {
+            if map.get(y, x) == Some(Tile::Food) {
+                food_index.push(Some(food.len()));
+                food.push((y, x));
+            } else {
+                food_index.push(None);
+            }
+        }
+    }
+    (food, food_index)
+}
+
+pub fn all_eaten(eaten: &[bool]) -> bool {
+    eaten.iter().all(|&e| e)
+}
+
+// The eaten food with food `f` eaten as well.
+pub fn eat(eaten: &Rc<Vec<bool>>, f: usize) -> Rc<Vec<bool>> {
+    let mut eaten = eaten.as_ref().clone();
+    eaten[f] = true;
+    Rc::new(eaten)
+}
+
+impl<'a> Graph for SnakeGraph<'a> {
+    type Node = State;
+    type Move = (char, i32, i32);
+
+    fn is_goal(&self, node: &State) -> bool {
+        all_eaten(&node.eaten)
+    }
+
+    fn neighbors(&self, node: &State) -> Vec<(Self::Move, State)> {
+        let mut res = Vec::new();
+        for &(m, dy, dx) in moves(self.map) {
+            let ((y, x), portal) = match self.map.step(node.head.0, node.head.1, dy, dx) {
+                Some(step) => step,
+                None => continue,
+            };
+            let i = self.index(y, x);
+            if node.visited[i] || portal.map_or(false, |(py, px)| node.visited[self.index(py, px)]) {
+                continue;
+            }
+            let time = if self.map.hazards.is_empty() { 0 } else { node.time + self.map.cost(y, x) };
+            if self.map.pit_at(y, x, time) {
+                continue;
+            }
+            let eaten = match self.food_index[i] {
+                Some(f) => eat(&node.eaten, f),
+                None => node.eaten.clone(),
+            };
+            // Food not eaten by its deadline is gone for good.
+            let expired = self.deadlines.iter().enumerate()
+                .any(|(f, d)| !node.eaten[f] && d.map_or(false, |d| time > d));
+            if expired {
+                continue;
+            }
+            let mut visited = node.visited.as_ref().clone();
+            visited[i] = true;
+            res.push(((m, y, x), State { head: (y, x), eaten: eaten, visited: Rc::new(visited), time: time }));
+        }
+        res
+    }
+}
+
+// Weight of the minimum spanning tree over the head and the remaining food,
+// using lower bounds of their distances: any path visiting all of them is at
+// least as long.
+pub fn mst(head: (i32, i32), dist: &FoodDistances, eaten: &[bool]) -> i32 {
+    let left: Vec<usize> = (0..eaten.len()).filter(|&f| !eaten[f]).collect();
+    let mut best: Vec<i32> = left.iter().map(|&f| dist.cell(head, f)).collect();
+    let mut in_tree = vec![false; left.len()];
+    let mut tot = 0;
+    for _ in 0..left.len() {
+        let (next, _) = best.iter().enumerate()
+            .filter(|&(i, _)| !in_tree[i])
+            .min_by_key(|&(_, &d)| d)
+            .unwrap();
+        tot += best[next];
+        in_tree[next] = true;
+        for i in 0..left.len() {
+            best[i] = ::std::cmp::min(best[i], dist.between(left[next], left[i]));
+        }
+    }
+    tot
+}
+
+pub fn h_mst(g: &SnakeGraph, node: &State) -> i32 {
+    mst(node.head, &g.dist, &node.eaten)
+}
+
+pub fn shortest_path(map: &Map) -> Option<Vec<(char, i32, i32)>> {
+    shortest_path_until(map, || false).unwrap()
+}
+
+// Gives up when stop() returns true, with the cost no solution is below.
+pub fn shortest_path_until<S: Fn() -> bool>(map: &Map, stop: S) -> Result<Option<Vec<(char, i32, i32)>>, i32> {
+    let graph = SnakeGraph::new(map);
+    let (_, res) = a_star_until(&graph, graph.start(), h_mst, |g, _, &(_, y, x)| g.map.cost(y, x), stop);
+    res.map(|path| path.map(|p| p.into_iter().map(|(m, _)| m).collect()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use map::from_text;
+    use std::iter;
+
+    #[test]
+    fn more_than_64_food_is_eaten() {
+        let border: String = iter::repeat('-').take(70).collect();
+        let row: String = iter::repeat('*').take(69).collect();
+        let map = from_text(&format!("+{}+\n|s{}|\n+{}+", border, row, border));
+        assert_eq!(shortest_path(&map).map(|p| map.path_cost(&p)), Some(69));
+    }
+
+    fn cost(text: &str) -> Option<i32> {
+        let map = from_text(text);
+        shortest_path(&map).map(|p| map.path_cost(&p))
+    }
+
+    #[test]
+    fn pits_are_only_avoided_while_open() {
+        let map = "+---+\n|s *|\n|  #|\n+---+";
+        assert_eq!(cost(map), Some(2));
+        // Open at times 0 and 1 out of every 4: (0, 1) is a pit when the
+        // head would get there, but not two moves later. Always open, it
+        // cuts the food off.
+        let path = shortest_path(&from_text(&format!("{}\npit 0 1 4 2", map))).unwrap();
+        assert_eq!(path.iter().map(|&(m, _, _)| m).collect::<String>(), "↓→↑→");
+        assert_eq!(cost(&format!("{}\npit 0 1 1 1", map)), None);
+    }
+
+    #[test]
+    fn food_has_to_be_eaten_before_its_deadline() {
+        let map = "+------+\n|*s   *|\n|      |\n+------+";
+        assert_eq!(cost(map), Some(8));
+        assert_eq!(cost(&format!("{}\nfood 0 5 5", map)), Some(11));
+        assert_eq!(cost(&format!("{}\nfood 0 5 3", map)), None);
+    }
+}
@@ -0,0 +1,132 @@
+use bfs::FoodDistances;
+use lib::astar::Graph;
+use map::{Map, Tile};
+use search::{all_eaten, eat, food_cells, moves, mst};
+use std::rc::Rc;
+
+// Game rules: the body follows the head, the tail frees its cell at every
+// move, and eating food makes the body one cell longer. The body starts
+// coiled up on the head and unrolls as the snake moves.
+#[derive(Clone, PartialEq, Eq, Hash)]
+pub struct Body {
+    pub cells: Rc<Vec<(i32, i32)>>,
+    pub eaten: Rc<Vec<bool>>,
+}
+
+impl Body {
+    pub fn head(&self) -> (i32, i32) {
+        self.cells[0]
+    }
+}
+
+pub struct BodyGraph<'a> {
+    pub map: &'a Map,
+    pub length: usize,
+    pub food: Vec<(i32, i32)>,
+    food_index: Vec<Option<usize>>,
+    dist: FoodDistances,
+}
+
+impl<'a> BodyGraph<'a> {
+    pub fn new(map: &'a Map, length: usize) -> BodyGraph<'a> {
+        let (food, food_index) = food_cells(map);
+        let dist = FoodDistances::new(map, &food);
+        BodyGraph { map: map, length: length, food: food, food_index: food_index, dist: dist }
+    }
+
+    pub fn start(&self) -> Body {
+        Body { cells: Rc::new(vec![self.map.head; self.length]), eaten: Rc::new(vec![false; self.food.len()]) }
+    }
+}
+
+impl<'a> Graph for BodyGraph<'a> {
+    type Node = Body;
+    type Move = (char, i32, i32);
+
+    fn is_goal(&self, node: &Body) -> bool {
+        all_eaten(&node.eaten)
+    }
+
+    fn neighbors(&self, node: &Body) -> Vec<(Self::Move, Body)> {
+        let mut res = Vec::new();
+        let (hy, hx) = node.head();
+        for &(m, dy, dx) in moves(self.map) {
+            let ((y, x), portal) = match self.map.step(hy, hx, dy, dx) {
+                Some(step) => step,
+                None => continue,
+            };
+            let food = self.food_index[(y * self.map.width + x) as usize]
+                .and_then(|f| if node.eaten[f] { None } else { Some(f) });
+            // Unless the snake grows, the tail moves away during this move.
+            let keep = if food.is_some() { node.cells.len() } else { node.cells.len() - 1 };
+            if node.cells[..keep].contains(&(y, x)) || portal.map_or(false, |p| node.cells[..keep].contains(&p)) {
+                continue;
+            }
+            let mut cells = Vec::with_capacity(keep + 1);
+            cells.push((y, x));
+            cells.extend_from_slice(&node.cells[..keep]);
+            let eaten = food.map_or(node.eaten.clone(), |f| eat(&node.eaten, f));
+            res.push(((m, y, x), Body { cells: Rc::new(cells), eaten: eaten }));
+        }
+        res
+    }
+}
+
+pub fn h_body(g: &BodyGraph, node: &Body) -> i32 {
+    mst(node.head(), &g.dist, &node.eaten)
+}
+
+// The state of the snake after each of the moves.
+pub fn replay(g: &BodyGraph, moves: &[(char, i32, i32)]) -> Vec<Body> {
+    let mut body = g.start();
+    let mut res = Vec::new();
+    for &m in moves {
+        body = g.neighbors(&body).into_iter().find(|&(n, _)| n == m).expect("Invalid move").1;
+        res.push(body.clone());
+    }
+    res
+}
+
+// The map as it looks with the snake in this state: eaten food is gone and
+// the body is drawn as snake tiles.
+pub fn map_at(g: &BodyGraph, node: &Body) -> Map {
+    let mut map = g.map.clone();
+    map.set(g.map.head.0, g.map.head.1, Tile::Empty);
+    for (f, &(y, x)) in g.food.iter().enumerate() {
+        if node.eaten[f] {
+            map.set(y, x, Tile::Empty);
+        }
+    }
+    for &(y, x) in node.cells.iter() {
+        map.set(y, x, Tile::Snake);
+    }
+    map.head = node.head();
+    map.food = node.eaten.iter().filter(|&&e| !e).count() as i32;
+    map
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use lib::astar::a_star_weighted;
+    use map::from_text;
+
+    fn solve(map: &Map, length: usize) -> Option<Vec<Body>> {
+        let graph = BodyGraph::new(map, length);
+        let (_, path) = a_star_weighted(&graph, graph.start(), h_body, |g, _, &(_, y, x)| g.map.cost(y, x));
+        path.map(|p| replay(&graph, &p.iter().map(|&(m, _)| m).collect::<Vec<_>>()))
+    }
+
+    #[test]
+    fn the_tail_frees_its_cell_and_food_grows_the_body() {
+        let map = from_text("+-----+\n|* s *|\n+-----+");
+        // A snake of length 1 turns back on the cell its tail just left.
+        let bodies = solve(&map, 1).unwrap();
+        assert_eq!(bodies.len(), 6);
+        let lengths: Vec<usize> = bodies.iter().map(|b| b.cells.len()).collect();
+        assert_eq!(lengths, vec![1, 2, 2, 2, 2, 3]);
+        assert_eq!(*bodies[2].cells, vec![(0, 1), (0, 0)]);
+        // One cell longer, it cannot turn back any more.
+        assert!(solve(&map, 2).is_none());
+    }
+}
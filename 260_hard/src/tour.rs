@@ -0,0 +1,103 @@
+use bfs::Bfs;
+use map::Map;
+use search::food_cells;
+
+// The subsets of food take 2^n entries.
+pub const MAX_FOOD: usize = 20;
+
+pub struct Tour {
+    pub order: Vec<(i32, i32)>,
+    pub path: Vec<(char, i32, i32)>,
+}
+
+// Visit the food in the order minimising the sum of the BFS distances,
+// found with Held-Karp dynamic programming over subsets of food. Legs are
+// stitched together from shortest paths, which may cross each other.
+// Returns None with more than MAX_FOOD food too.
+pub fn held_karp(map: &Map) -> Option<Tour> {
+    let (food, _) = food_cells(map);
+    let n = food.len();
+    if n > MAX_FOOD {
+        return None;
+    }
+    if n == 0 {
+        return Some(Tour { order: vec![], path: vec![] });
+    }
+    let from_head = Bfs::new(map, map.head);
+    let from_food: Vec<Bfs> = food.iter().map(|&f| Bfs::new(map, f)).collect();
+    if food.iter().any(|&f| from_head.distance(f).is_none()) {
+        return None;
+    }
+
+    let inf = ::std::i32::MAX;
+    let full = (1 << n) - 1;
+    // best[mask][i]: shortest walk from the head eating the food in mask,
+    // ending on food i.
+    let mut best = vec![vec![inf; n]; 1 << n];
+    let mut prev = vec![vec![n; n]; 1 << n];
+    for i in 0..n {
+        best[1 << i][i] = from_head.distance(food[i]).unwrap();
+    }
+    for mask in 1..(full + 1) {
+        for i in 0..n {
+            if mask & (1 << i) == 0 || best[mask][i] == inf {
+                continue;
+            }
+            for j in 0..n {
+                if mask & (1 << j) != 0 {
+                    continue;
+                }
+                // One-way tiles can make a leg impossible in this direction.
+                let d = match from_food[i].distance(food[j]) {
+                    Some(d) => best[mask][i] + d,
+                    None => continue,
+                };
+                let next = mask | (1 << j);
+                if d < best[next][j] {
+                    best[next][j] = d;
+                    prev[next][j] = i;
+                }
+            }
+        }
+    }
+
+    let mut last = (0..n).min_by_key(|&i| best[full][i]).unwrap();
+    if best[full][last] == inf {
+        return None;
+    }
+    let mut mask = full;
+    let mut order = Vec::new();
+    while last < n {
+        order.push(last);
+        let p = prev[mask][last];
+        mask &= !(1 << last);
+        last = p;
+    }
+    order.reverse();
+
+    let mut path = from_head.path_to(food[order[0]]);
+    for w in order.windows(2) {
+        path.extend(from_food[w[0]].path_to(food[w[1]]));
+    }
+    Some(Tour { order: order.iter().map(|&i| food[i]).collect(), path: path })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use map::from_text;
+
+    #[test]
+    fn legs_blocked_by_one_way_tiles_are_skipped() {
+        let tour = held_karp(&from_text("+----+\n|*s>*|\n+----+")).unwrap();
+        assert_eq!(tour.order, vec![(0, 0), (0, 3)]);
+        assert!(held_karp(&from_text("+-----+\n|*<s>*|\n+-----+")).is_none());
+    }
+
+    #[test]
+    fn too_much_food_gives_no_tour() {
+        let map = from_text("+----------------------+\n|s*********************|\n+----------------------+");
+        assert_eq!(map.food as usize, MAX_FOOD + 1);
+        assert!(held_karp(&map).is_none());
+    }
+}
@@ -0,0 +1,90 @@
+// Minimum-cost assignment (Hungarian algorithm) on a square cost matrix,
+// returning the total cost of the best assignment.
+pub fn min_cost(cost: &[Vec<i32>]) -> i32 {
+    let n = cost.len();
+    let inf = ::std::i32::MAX / 2;
+    // Potentials and matching use 1-based indices, 0 is a sentinel column.
+    let mut u = vec![0; n + 1];
+    let mut v = vec![0; n + 1];
+    let mut p = vec![0; n + 1];
+    let mut way = vec![0; n + 1];
+
+    for i in 1..(n + 1) {
+        p[0] = i;
+        let mut j0 = 0;
+        let mut minv = vec![inf; n + 1];
+        let mut used = vec![false; n + 1];
+        loop {
+            used[j0] = true;
+            let i0 = p[j0];
+            let mut delta = inf;
+            let mut j1 = 0;
+            for j in 1..(n + 1) {
+                if !used[j] {
+                    let cur = cost[i0 - 1][j - 1] - u[i0] - v[j];
+                    if cur < minv[j] {
+                        minv[j] = cur;
+                        way[j] = j0;
+                    }
+                    if minv[j] < delta {
+                        delta = minv[j];
+                        j1 = j;
+                    }
+                }
+            }
+            for j in 0..(n + 1) {
+                if used[j] {
+                    u[p[j]] += delta;
+                    v[j] -= delta;
+                } else {
+                    minv[j] -= delta;
+                }
+            }
+            j0 = j1;
+            if p[j0] == 0 {
+                break;
+            }
+        }
+        loop {
+            let j1 = way[j0];
+            p[j0] = p[j1];
+            j0 = j1;
+            if j0 == 0 {
+                break;
+            }
+        }
+    }
+    (1..(n + 1)).fold(0, |acc, j| acc + cost[p[j] - 1][j - 1])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use perm::{factorial, unrank};
+    use rand::{Rng, SeedableRng, XorShiftRng};
+
+    // The cheapest of all the n! assignments.
+    fn brute_force(cost: &[Vec<i32>]) -> i32 {
+        let n = cost.len();
+        (0..factorial(n))
+            .map(|r| unrank(r, n).iter().enumerate().fold(0, |acc, (i, &j)| acc + cost[i][j as usize - 1]))
+            .min()
+            .unwrap()
+    }
+
+    #[test]
+    fn min_cost_matches_brute_force() {
+        let mut rng = XorShiftRng::from_seed([1, 2, 3, 4]);
+        for n in 1..6 {
+            for _ in 0..50 {
+                let cost: Vec<Vec<i32>> = (0..n).map(|_| (0..n).map(|_| rng.gen_range(0, 20)).collect()).collect();
+                assert_eq!(min_cost(&cost), brute_force(&cost), "{:?}", cost);
+            }
+        }
+    }
+
+    #[test]
+    fn empty_matrix_costs_nothing() {
+        assert_eq!(min_cost(&[]), 0);
+    }
+}
@@ -0,0 +1,132 @@
+use lib::astar::{Graph, a_star};
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::fs::File;
+use std::io;
+use std::io::prelude::*;
+use std::rc::Rc;
+use world::{Coord, World, WNode, h1, parse_input_file};
+
+struct StateGraph {
+    nodes: Vec<WNode>,
+    ids: HashMap<WNode, usize>,
+    edges: Vec<(usize, usize, (Coord, Coord))>,
+}
+
+impl StateGraph {
+    fn id(&mut self, node: &WNode) -> (usize, bool) {
+        if let Some(&id) = self.ids.get(node) {
+            return (id, false);
+        }
+        let id = self.nodes.len();
+        self.nodes.push(node.clone());
+        self.ids.insert(node.clone(), id);
+        (id, true)
+    }
+}
+
+fn edge_key(a: usize, b: usize) -> (usize, usize) {
+    if a < b { (a, b) } else { (b, a) }
+}
+
+// Every state within `depth` swaps of the start, plus the optimal path so
+// that it is always drawn in full.
+fn expand(world: &World, depth: usize, path: &[((Coord, Coord), WNode, WNode)]) -> StateGraph {
+    let mut g = StateGraph { nodes: Vec::new(), ids: HashMap::new(), edges: Vec::new() };
+    let mut queue = VecDeque::new();
+    let mut seen_edges = HashSet::new();
+
+    let (start, _) = g.id(&Rc::new(world.data.clone()));
+    queue.push_back((start, 0));
+    while let Some((id, d)) = queue.pop_front() {
+        if d == depth {
+            continue;
+        }
+        let node = g.nodes[id].clone();
+        for (mov, next) in world.neighbors(&node) {
+            let (next_id, new) = g.id(&next);
+            if new {
+                queue.push_back((next_id, d + 1));
+            }
+            if seen_edges.insert(edge_key(id, next_id)) {
+                g.edges.push((id, next_id, mov));
+            }
+        }
+    }
+    for &(mov, ref from, ref to) in path {
+        let (a, _) = g.id(from);
+        let (b, _) = g.id(to);
+        if seen_edges.insert(edge_key(a, b)) {
+            g.edges.push((a, b, mov));
+        }
+    }
+    g
+}
+
+fn label(world: &World, node: &WNode) -> String {
+    let rows: Vec<String> = node.chunks(world.width)
+        .map(|r| r.iter().map(|v| v.to_string()).collect::<Vec<_>>().join(" "))
+        .collect();
+    rows.join("\\n")
+}
+
+pub fn write_dot<W: Write>(out: &mut W, world: &World, depth: usize) -> io::Result<()> {
+    let path: Vec<((Coord, Coord), WNode, WNode)> = match a_star(world, Rc::new(world.data.clone()), h1) {
+        (_, Some(p)) => {
+            let mut nodes: Vec<WNode> = p.iter().map(|&(_, ref n)| n.clone()).collect();
+            nodes.push(Rc::new(world.sorted().data));
+            p.iter().zip(nodes.windows(2)).map(|(&(mov, _), w)| (mov, w[0].clone(), w[1].clone())).collect()
+        },
+        (_, None) => Vec::new(),
+    };
+    let g = expand(world, depth, &path);
+    let path_edges: HashSet<(usize, usize)> = path.iter()
+        .map(|&(_, ref a, ref b)| edge_key(g.ids[a], g.ids[b]))
+        .collect();
+    let on_path: HashSet<usize> = path_edges.iter().flat_map(|&(a, b)| vec![a, b]).collect();
+
+    try!(writeln!(out, "graph states {{"));
+    try!(writeln!(out, "    node [shape=box, fontname=monospace];"));
+    for (id, node) in g.nodes.iter().enumerate() {
+        let mut attrs = format!("label=\"{}\"", label(world, node));
+        if id == 0 {
+            attrs.push_str(", style=bold");
+        }
+        if world.is_goal(node) {
+            attrs.push_str(", peripheries=2");
+        }
+        if on_path.contains(&id) {
+            attrs.push_str(", color=red");
+        }
+        try!(writeln!(out, "    n{} [{}];", id, attrs));
+    }
+    for &(a, b, (c1, c2)) in &g.edges {
+        let mut attrs = format!("label=\"({},{})-({},{})\"", c1.0, c1.1, c2.0, c2.1);
+        if path_edges.contains(&edge_key(a, b)) {
+            attrs.push_str(", color=red, penwidth=2");
+        }
+        try!(writeln!(out, "    n{} -- n{} [{}];", a, b, attrs));
+    }
+    writeln!(out, "}}")
+}
+
+fn usage() -> ! {
+    println!("Usage: 262_hard dot <boards> <depth> <output.dot>");
+    ::std::process::exit(1);
+}
+
+pub fn run(args: &[String]) {
+    if args.len() != 3 {
+        usage();
+    }
+    let depth = args[1].parse::<usize>().unwrap_or_else(|_| usage());
+    let boards = match parse_input_file(args[0].as_str()) {
+        Ok(boards) => boards,
+        Err(e) => {
+            println!("{}: {}", args[0], e);
+            ::std::process::exit(1);
+        },
+    };
+    let world = boards.first().expect("No board found");
+    let mut f = File::create(args[2].as_str()).expect("Cannot create output file");
+    write_dot(&mut f, world, depth).expect("Cannot write output file");
+}
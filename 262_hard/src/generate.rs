@@ -0,0 +1,112 @@
+use lib::astar::a_star;
+use rand::{Rng, SeedableRng, XorShiftRng};
+use std::io;
+use std::io::prelude::*;
+use std::rc::Rc;
+use world::{World, h1};
+
+pub struct Instance {
+    pub world: World,
+    pub swaps: usize,
+    pub distance: Option<usize>,
+}
+
+fn create_rng(seed: u32) -> XorShiftRng {
+    XorShiftRng::from_seed([seed, seed ^ 0x9e37_79b9, 0x243f_6a88, 0x85a3_08d3])
+}
+
+// Start from the sorted board and apply k random swaps, never undoing the
+// previous swap right away.
+pub fn scramble<R: Rng>(rng: &mut R, size: usize, k: usize) -> World {
+    let mut world = World::goal(size);
+    let mut last = None;
+    let mut done = 0;
+
+    while done < k {
+        let n = rng.gen_range(0, world.pairs.len());
+        if Some(n) == last {
+            continue;
+        }
+        let (c1, c2) = world.pairs[n];
+        world.swap(c1, c2);
+        last = Some(n);
+        done += 1;
+    }
+    world
+}
+
+pub fn distance(world: &World) -> Option<usize> {
+    match a_star(world, Rc::new(world.data.clone()), h1) {
+        (_, Some(path)) => Some(path.len()),
+        (_, None) => None,
+    }
+}
+
+// Scrambles tried for each board with `exact` before giving up.
+const MAX_ATTEMPTS: usize = 1000;
+
+// Generate `count` boards scrambled with `k` swaps. When `exact` is set,
+// scrambles whose optimal distance is shorter than `k` are discarded, and
+// the boards found so far are returned as an error when MAX_ATTEMPTS
+// scrambles in a row are, as they all are once `k` is above the diameter.
+pub fn generate(size: usize, k: usize, count: usize, seed: u32, exact: bool) -> Result<Vec<Instance>, Vec<Instance>> {
+    let mut rng = create_rng(seed);
+    let mut instances = Vec::new();
+    let mut attempts = 0;
+
+    while instances.len() < count {
+        if attempts == MAX_ATTEMPTS {
+            return Err(instances);
+        }
+        let world = scramble(&mut rng, size, k);
+        let distance = if exact { distance(&world) } else { None };
+        if exact && distance != Some(k) {
+            attempts += 1;
+            continue;
+        }
+        instances.push(Instance { world: world, swaps: k, distance: distance });
+        attempts = 0;
+    }
+    Ok(instances)
+}
+
+pub fn write_instances<W: Write>(out: &mut W, instances: &[Instance]) -> io::Result<()> {
+    for (n, inst) in instances.iter().enumerate() {
+        if n > 0 {
+            try!(writeln!(out, ""));
+        }
+        match inst.distance {
+            Some(d) => try!(writeln!(out, "# board {}: {} swaps, distance {}", n + 1, inst.swaps, d)),
+            None => try!(writeln!(out, "# board {}: {} swaps", n + 1, inst.swaps)),
+        }
+        try!(inst.world.write(out));
+    }
+    Ok(())
+}
+
+fn usage() -> ! {
+    println!("Usage: 262_hard generate <size> <swaps> <count> [seed] [--exact]");
+    ::std::process::exit(1);
+}
+
+pub fn run(args: &[String]) {
+    let exact = args.iter().any(|a| a == "--exact");
+    let nums: Vec<u32> = args.iter()
+        .filter(|a| !a.starts_with("--"))
+        .map(|a| a.parse::<u32>().unwrap_or_else(|_| usage()))
+        .collect();
+    // A board needs two cells to swap anything.
+    if nums.len() < 3 || nums.len() > 4 || nums[0] < 2 {
+        usage();
+    }
+    let seed = if nums.len() == 4 { nums[3] } else { 0 };
+    match generate(nums[0] as usize, nums[1] as usize, nums[2] as usize, seed, exact) {
+        Ok(instances) => write_instances(&mut io::stdout(), &instances).unwrap(),
+        Err(instances) => {
+            write_instances(&mut io::stdout(), &instances).unwrap();
+            writeln!(io::stderr(), "No board at distance {} in {} scrambles, {} of {} generated",
+                     nums[1], MAX_ATTEMPTS, instances.len(), nums[2]).unwrap();
+            ::std::process::exit(1);
+        },
+    }
+}
@@ -0,0 +1,97 @@
+use rustc_serialize::json::{Json, ToJson};
+use std::collections::BTreeMap;
+use time::Duration;
+use world::{Coord, Cost, World, WNode};
+
+#[derive(Copy, Clone, PartialEq)]
+pub enum Format {
+    Text,
+    Json,
+    Compact,
+}
+
+impl Format {
+    pub fn parse(s: &str) -> Option<Format> {
+        match s {
+            "text" => Some(Format::Text),
+            "json" => Some(Format::Json),
+            "compact" => Some(Format::Compact),
+            _ => None,
+        }
+    }
+}
+
+pub struct Solution<'a> {
+    pub start: &'a World,
+    pub path: Option<Vec<((Coord, Coord), WNode)>>,
+    pub nodes: usize,
+    pub elapsed: Duration,
+}
+
+fn rows(width: usize, data: &[i32]) -> Json {
+    data.chunks(width).map(|r| r.to_vec()).collect::<Vec<_>>().to_json()
+}
+
+// One swap per token, e.g. "(3,2)-(3,3)", which the verifier reads back.
+pub fn compact(path: &[((Coord, Coord), WNode)]) -> String {
+    let moves: Vec<String> = path.iter()
+        .map(|&((c0, c1), _)| format!("({},{})-({},{})", c0.0, c0.1, c1.0, c1.1))
+        .collect();
+    moves.join(" ")
+}
+
+pub fn to_json(sol: &Solution) -> Json {
+    let mut board = sol.start.clone();
+    let mut swaps = Vec::new();
+
+    // Path nodes are the boards before each swap, so replay the swaps to
+    // report the resulting boards.
+    for &((c0, c1), _) in sol.path.iter().flat_map(|p| p.iter()) {
+        board.swap(c0, c1);
+        let mut swap = BTreeMap::new();
+        swap.insert("swap".to_string(), ((c0.0, c0.1), (c1.0, c1.1)).to_json());
+        swap.insert("board".to_string(), rows(board.width, &board.data));
+        swaps.push(Json::Object(swap));
+    }
+
+    let mut obj = BTreeMap::new();
+    obj.insert("start".to_string(), rows(sol.start.width, &sol.start.data));
+    obj.insert("solved".to_string(), sol.path.is_some().to_json());
+    obj.insert("moves".to_string(), swaps.len().to_json());
+    obj.insert("cost".to_string(), sol.path.as_ref().map(|p| sol.start.path_cost(p)).to_json());
+    obj.insert("swaps".to_string(), Json::Array(swaps));
+    obj.insert("nodes".to_string(), sol.nodes.to_json());
+    obj.insert("elapsed_ms".to_string(), (sol.elapsed.num_microseconds().unwrap_or(0) as f64 / 1000.0).to_json());
+    Json::Object(obj)
+}
+
+fn print_text(sol: &Solution) {
+    if let Some(ref path) = sol.path {
+        for &((c0, c1), ref node) in path {
+            let mut nw = sol.start.clone();
+            nw.data = node.as_ref().clone();
+            println!("\nSwapping ({}, {}) and ({}, {})", c0.0, c0.1, c1.0, c1.1);
+            nw.print();
+        }
+        if sol.start.cost == Cost::Unit {
+            println!("{} grids analysed, solution has {} moves", sol.nodes, path.len());
+        } else {
+            println!("{} grids analysed, solution has {} moves and costs {}", sol.nodes, path.len(),
+                     sol.start.path_cost(path));
+        }
+    } else {
+        println!("Nope :(")
+    }
+    println!("\nElapsed time {}\n", sol.elapsed);
+}
+
+pub fn print_solution(format: Format, sol: &Solution) {
+    match format {
+        Format::Text => print_text(sol),
+        Format::Json => println!("{}", to_json(sol)),
+        Format::Compact => match sol.path {
+            Some(ref path) => println!("{}", compact(path)),
+            None => println!("-"),
+        },
+    }
+}
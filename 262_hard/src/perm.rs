@@ -0,0 +1,48 @@
+// Ranking of permutations of 1..n through their Lehmer code, so that
+// every board of a given size maps to a unique index in 0..n!.
+
+pub fn factorial(n: usize) -> usize {
+    (1..(n + 1)).fold(1, |acc, x| acc * x)
+}
+
+pub fn rank(perm: &[i32]) -> usize {
+    let n = perm.len();
+    let mut r = 0;
+    for i in 0..n {
+        let smaller = perm[(i + 1)..].iter().filter(|&&v| v < perm[i]).count();
+        r += smaller * factorial(n - 1 - i);
+    }
+    r
+}
+
+pub fn unrank(mut r: usize, n: usize) -> Vec<i32> {
+    let mut left: Vec<i32> = (1..(n + 1) as i32).collect();
+    let mut perm = Vec::with_capacity(n);
+    for i in 0..n {
+        let f = factorial(n - 1 - i);
+        perm.push(left.remove(r / f));
+        r %= f;
+    }
+    perm
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn unrank_inverts_rank() {
+        for n in 0..6 {
+            for r in 0..factorial(n) {
+                assert_eq!(rank(&unrank(r, n)), r);
+            }
+        }
+    }
+
+    #[test]
+    fn sorted_is_first_and_reversed_is_last() {
+        assert_eq!(rank(&[1, 2, 3, 4]), 0);
+        assert_eq!(rank(&[4, 3, 2, 1]), factorial(4) - 1);
+        assert_eq!(unrank(1, 3), vec![1, 3, 2]);
+    }
+}
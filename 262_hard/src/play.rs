@@ -0,0 +1,140 @@
+use lib::astar::{Graph, a_star};
+use std::io;
+use std::io::prelude::*;
+use std::rc::Rc;
+use world::{Coord, World, h1, parse_input_file};
+
+const CURSOR: &'static str = "\x1b[7m";
+const SELECTED: &'static str = "\x1b[1;33m";
+const HINT: &'static str = "\x1b[1;32m";
+const RESET: &'static str = "\x1b[0m";
+
+struct Player {
+    world: World,
+    history: Vec<(Coord, Coord)>,
+    cursor: Coord,
+    selected: Option<Coord>,
+    hint: Option<(Coord, Coord)>,
+}
+
+fn same(c1: Coord, c2: Coord) -> bool {
+    c1.0 == c2.0 && c1.1 == c2.1
+}
+
+fn adjacent(c1: Coord, c2: Coord) -> bool {
+    (c1.0 as i32 - c2.0 as i32).abs() + (c1.1 as i32 - c2.1 as i32).abs() == 1
+}
+
+impl Player {
+    fn new(world: World) -> Player {
+        Player { world: world, history: Vec::new(), cursor: Coord(0, 0), selected: None, hint: None }
+    }
+
+    fn is_solved(&self) -> bool {
+        self.world.is_goal(&Rc::new(self.world.data.clone()))
+    }
+
+    // Same layout as World::print, with the cursor, the selection and the
+    // hint highlighted.
+    fn print(&self) {
+        for y in 0..self.world.height {
+            for x in 0..self.world.width {
+                let c = Coord(y, x);
+                let is_hint = self.hint.map_or(false, |(h1, h2)| same(c, h1) || same(c, h2));
+                let style = if same(c, self.cursor) {
+                    CURSOR
+                } else if self.selected.map_or(false, |s| same(c, s)) {
+                    SELECTED
+                } else if is_hint {
+                    HINT
+                } else {
+                    ""
+                };
+                print!("{}{:3}{}", style, self.world.get(c), if style.is_empty() { "" } else { RESET });
+            }
+            println!("");
+        }
+        println!("Moves: {}", self.history.len());
+    }
+
+    fn move_cursor(&mut self, dy: i32, dx: i32) {
+        let (height, width) = (self.world.height as i32, self.world.width as i32);
+        let y = (self.cursor.0 as i32 + dy + height) % height;
+        let x = (self.cursor.1 as i32 + dx + width) % width;
+        self.cursor = Coord(y as usize, x as usize);
+    }
+
+    fn select(&mut self) {
+        let c = self.cursor;
+        match self.selected {
+            Some(s) if same(s, c) => self.selected = None,
+            Some(s) if adjacent(s, c) => {
+                self.world.swap(s, c);
+                self.history.push((s, c));
+                self.selected = None;
+                self.hint = None;
+            },
+            _ => self.selected = Some(c),
+        }
+    }
+
+    fn undo(&mut self) {
+        if let Some((c1, c2)) = self.history.pop() {
+            self.world.swap(c1, c2);
+            self.hint = None;
+        }
+    }
+
+    fn hint(&mut self) {
+        let (_, path) = a_star(&self.world, Rc::new(self.world.data.clone()), h1);
+        self.hint = path.and_then(|p| p.first().map(|&(mov, _)| mov));
+        if let Some(p) = self.hint {
+            println!("Hint: swap ({}, {}) and ({}, {})", (p.0).0, (p.0).1, (p.1).0, (p.1).1);
+        }
+    }
+}
+
+fn print_help() {
+    println!("w/a/s/d: move, e: select (select a neighbor to swap), u: undo, h: hint, q: quit");
+}
+
+pub fn play(world: World) {
+    let mut player = Player::new(world);
+    let stdin = io::stdin();
+
+    print_help();
+    player.print();
+    for line in stdin.lock().lines() {
+        let line = line.unwrap();
+        for c in line.chars() {
+            match c {
+                'w' => player.move_cursor(-1, 0),
+                's' => player.move_cursor(1, 0),
+                'a' => player.move_cursor(0, -1),
+                'd' => player.move_cursor(0, 1),
+                'e' | ' ' => player.select(),
+                'u' => player.undo(),
+                'h' => player.hint(),
+                'q' => return,
+                _ => print_help(),
+            }
+        }
+        println!("");
+        player.print();
+        if player.is_solved() {
+            println!("Solved in {} moves!", player.history.len());
+            return;
+        }
+    }
+}
+
+pub fn run(args: &[String]) {
+    let world = match args.first() {
+        Some(path) => {
+            let boards = parse_input_file(path.as_str()).expect("File not readable");
+            boards.into_iter().next().expect("No board found")
+        },
+        None => World::new(3, &[3, 2, 1, 9, 6, 4, 7, 8, 5]),
+    };
+    play(world);
+}
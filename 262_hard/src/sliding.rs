@@ -0,0 +1,231 @@
+use lib::astar::{Graph, a_star};
+use output::{Format, Solution, print_solution};
+use std::rc::Rc;
+use time::PreciseTime;
+use world::{Coord, World, WNode, parse_input_file};
+
+// The classic sliding-tile puzzle on the same boards: the highest value is
+// the blank, and only swaps involving the blank are allowed.
+pub struct Sliding {
+    pub world: World,
+}
+
+impl Sliding {
+    pub fn new(world: World) -> Sliding {
+        Sliding { world: world }
+    }
+
+    fn blank(&self) -> i32 {
+        self.world.data.len() as i32
+    }
+
+    // Inversions among the tiles, plus the blank row counted from the bottom
+    // when the width is even.
+    pub fn is_solvable(&self) -> bool {
+        let size = self.world.width;
+        let blank = self.blank();
+        let tiles: Vec<i32> = self.world.data.iter().cloned().filter(|&v| v != blank).collect();
+        let mut inversions = 0;
+        for i in 0..tiles.len() {
+            inversions += tiles[(i + 1)..].iter().filter(|&&v| v < tiles[i]).count();
+        }
+        if size % 2 == 1 {
+            inversions % 2 == 0
+        } else {
+            let blank_pos = self.world.data.iter().position(|&v| v == blank).unwrap();
+            let row_from_bottom = size - blank_pos / size;
+            (inversions + row_from_bottom) % 2 == 1
+        }
+    }
+}
+
+impl Graph for Sliding {
+    type Node = WNode;
+    type Move = (Coord, Coord);
+
+    fn is_goal(&self, node: &Self::Node) -> bool {
+        self.world.is_goal(node)
+    }
+
+    fn neighbors(&self, node: &Self::Node) -> Vec<(Self::Move, Self::Node)> {
+        let size = self.world.width;
+        let blank = node.iter().position(|&v| v == self.blank()).unwrap();
+        let b = Coord(blank / size, blank % size);
+        let mut res = Vec::new();
+        for &(c1, c2) in &self.world.pairs {
+            if (c1.0 == b.0 && c1.1 == b.1) || (c2.0 == b.0 && c2.1 == b.1) {
+                let mut new = node.as_ref().clone();
+                new.swap(c1.0 * size + c1.1, c2.0 * size + c2.1);
+                res.push(((c1, c2), Rc::new(new)));
+            }
+        }
+        res
+    }
+}
+
+pub fn manhattan(s: &Sliding, n: &WNode) -> i32 {
+    let size = s.world.width as i32;
+    let mut tot = 0;
+    for (pos, &v) in n.iter().enumerate() {
+        if v == s.blank() {
+            continue;
+        }
+        let pos = pos as i32;
+        let target = v - 1;
+        tot += (target / size - pos / size).abs() + (target % size - pos % size).abs();
+    }
+    tot
+}
+
+// Extra moves needed by the tiles of one row or column that are in their
+// target line but in reversed order: every tile that has to leave the line
+// costs two more moves. `line` holds the target offsets along the line.
+fn line_conflicts(line: &[i32]) -> i32 {
+    let mut line = line.to_vec();
+    let mut extra = 0;
+    loop {
+        let conflicts: Vec<usize> = (0..line.len())
+            .map(|i| (0..line.len()).filter(|&j| {
+                line[i] >= 0 && line[j] >= 0 && ((i < j && line[i] > line[j]) || (i > j && line[i] < line[j]))
+            }).count())
+            .collect();
+        let (worst, &count) = conflicts.iter().enumerate().max_by_key(|&(_, c)| *c).unwrap();
+        if count == 0 {
+            return extra;
+        }
+        line[worst] = -1;
+        extra += 2;
+    }
+}
+
+pub fn linear_conflict(s: &Sliding, n: &WNode) -> i32 {
+    let size = s.world.width;
+    let mut extra = 0;
+    for i in 0..size {
+        let mut row = vec![-1; size];
+        let mut col = vec![-1; size];
+        for j in 0..size {
+            let r = n[i * size + j];
+            if r != s.blank() && (r - 1) as usize / size == i {
+                row[j] = (r - 1) % size as i32;
+            }
+            let c = n[j * size + i];
+            if c != s.blank() && (c - 1) as usize % size == i {
+                col[j] = (c - 1) / size as i32;
+            }
+        }
+        extra += line_conflicts(&row) + line_conflicts(&col);
+    }
+    manhattan(s, n) + extra
+}
+
+fn usage() -> ! {
+    println!("Usage: 262_hard sliding <boards> [manhattan|conflict]");
+    ::std::process::exit(1);
+}
+
+pub fn run(args: &[String], format: Format) {
+    if args.is_empty() || args.len() > 2 {
+        usage();
+    }
+    let h: fn(&Sliding, &WNode) -> i32 = match args.get(1).map_or("conflict", |s| s.as_str()) {
+        "manhattan" => manhattan,
+        "conflict" => linear_conflict,
+        _ => usage(),
+    };
+    let boards = parse_input_file(args[0].as_str()).expect("File not readable");
+    for w in boards {
+        if !w.is_square() {
+            w.print();
+            println!("Only n×n boards are supported\n");
+            continue;
+        }
+        if !w.is_permutation() {
+            w.print();
+            println!("Not a permutation of 1..{}\n", w.data.len());
+            continue;
+        }
+        let s = Sliding::new(w);
+        if !s.is_solvable() {
+            s.world.print();
+            println!("Not solvable\n");
+            continue;
+        }
+        let start_time = PreciseTime::now();
+        let (n, path) = a_star(&s, Rc::new(s.world.data.clone()), h);
+        print_solution(format, &Solution {
+            start: &s.world,
+            path: path,
+            nodes: n,
+            elapsed: start_time.to(PreciseTime::now()),
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::{Rng, SeedableRng, XorShiftRng};
+
+    // The goal of the given size after `k` random moves of the blank, which
+    // is always solvable.
+    fn scrambled<R: Rng>(rng: &mut R, size: usize, k: usize) -> Sliding {
+        let goal = World::goal(size);
+        let s = Sliding::new(goal.clone());
+        let mut node = Rc::new(goal.data);
+        for _ in 0..k {
+            let mut next = s.neighbors(&node);
+            let i = rng.gen_range(0, next.len());
+            node = next.swap_remove(i).1;
+        }
+        Sliding::new(World::new(size, &node))
+    }
+
+    fn board(size: usize, values: &[i32]) -> Sliding {
+        Sliding::new(World::new(size, values))
+    }
+
+    #[test]
+    fn known_boards_have_the_right_parity() {
+        assert!(board(3, &[1, 2, 3, 4, 5, 6, 7, 8, 9]).is_solvable());
+        assert!(!board(3, &[2, 1, 3, 4, 5, 6, 7, 8, 9]).is_solvable());
+        assert!(board(3, &[1, 2, 3, 4, 5, 9, 7, 8, 6]).is_solvable());
+        let goal: Vec<i32> = (1..17).collect();
+        assert!(board(4, &goal).is_solvable());
+        // Sam Loyd's 14-15 puzzle.
+        let mut loyd = goal.clone();
+        loyd.swap(13, 14);
+        assert!(!board(4, &loyd).is_solvable());
+        // The blank moved up a row.
+        let mut up = goal.clone();
+        up.swap(11, 15);
+        assert!(board(4, &up).is_solvable());
+    }
+
+    #[test]
+    fn scrambled_boards_are_solvable() {
+        let mut rng = XorShiftRng::from_seed([5, 6, 7, 8]);
+        for &size in &[2, 3, 4] {
+            for k in 0..30 {
+                let s = scrambled(&mut rng, size, k);
+                assert!(s.is_solvable(), "{:?}", s.world.data);
+            }
+        }
+    }
+
+    #[test]
+    fn linear_conflict_is_between_manhattan_and_the_distance() {
+        let mut rng = XorShiftRng::from_seed([9, 10, 11, 12]);
+        for &size in &[3, 4] {
+            for k in 0..20 {
+                let s = scrambled(&mut rng, size, k);
+                let node = Rc::new(s.world.data.clone());
+                assert!(linear_conflict(&s, &node) >= manhattan(&s, &node), "{:?}", s.world.data);
+                if size == 3 {
+                    let (_, path) = a_star(&s, node.clone(), manhattan);
+                    assert!(linear_conflict(&s, &node) <= path.unwrap().len() as i32, "{:?}", s.world.data);
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,154 @@
+use perm::{factorial, rank, unrank};
+use std::collections::VecDeque;
+use std::fs::File;
+use std::io;
+use std::io::prelude::*;
+use world::{Coord, World, create_pairs, parse_input_file};
+
+pub const UNKNOWN: u8 = 255;
+
+// Exact distance to the goal of every board of the given size, indexed by
+// permutation rank. Swaps are reversible, so a single BFS from the goal
+// covers the whole state space.
+pub fn build(size: usize) -> Vec<u8> {
+    let n = size * size;
+    let pairs = create_pairs(size, size);
+    let mut table = vec![UNKNOWN; factorial(n)];
+    let mut queue = VecDeque::new();
+    let goal = rank(&World::goal(size).data);
+
+    table[goal] = 0;
+    queue.push_back(goal);
+    while let Some(r) = queue.pop_front() {
+        let d = table[r];
+        let mut perm = unrank(r, n);
+        for &(c1, c2) in &pairs {
+            perm.swap(c1.0 * size + c1.1, c2.0 * size + c2.1);
+            let next = rank(&perm);
+            if table[next] == UNKNOWN {
+                table[next] = d + 1;
+                queue.push_back(next);
+            }
+            perm.swap(c1.0 * size + c1.1, c2.0 * size + c2.1);
+        }
+    }
+    table
+}
+
+pub fn size_of(table: &[u8]) -> Option<usize> {
+    (1..5).find(|&s| factorial(s * s) == table.len())
+}
+
+pub fn histogram(table: &[u8]) -> Vec<usize> {
+    let mut hist = Vec::new();
+    for &d in table.iter().filter(|&&d| d != UNKNOWN) {
+        if hist.len() <= d as usize {
+            hist.resize(d as usize + 1, 0);
+        }
+        hist[d as usize] += 1;
+    }
+    hist
+}
+
+pub fn save(path: &str, table: &[u8]) -> io::Result<()> {
+    let mut f = try!(File::create(path));
+    f.write_all(table)
+}
+
+pub fn load(path: &str) -> io::Result<Vec<u8>> {
+    let mut f = try!(File::open(path));
+    let mut table = Vec::new();
+    try!(f.read_to_end(&mut table));
+    // The goal is the only board at distance 0.
+    let valid = size_of(&table).map_or(false, |s| table[rank(&World::goal(s).data)] == 0);
+    if !valid {
+        return Err(io::Error::new(io::ErrorKind::InvalidData, "Not a distance table"));
+    }
+    Ok(table)
+}
+
+pub fn lookup(table: &[u8], world: &World) -> u8 {
+    table[rank(&world.data)]
+}
+
+// Follow strictly decreasing distances down to the goal.
+pub fn optimal_path(table: &[u8], world: &World) -> Vec<(Coord, Coord)> {
+    let mut w = world.clone();
+    let mut path = Vec::new();
+    let mut d = lookup(table, &w);
+
+    while d > 0 && d != UNKNOWN {
+        for &(c1, c2) in &world.pairs {
+            w.swap(c1, c2);
+            if lookup(table, &w) == d - 1 {
+                path.push((c1, c2));
+                break;
+            }
+            w.swap(c1, c2);
+        }
+        d -= 1;
+    }
+    path
+}
+
+fn print_stats(table: &[u8]) {
+    let hist = histogram(table);
+    let size = size_of(table).unwrap();
+    if hist.is_empty() {
+        println!("{}x{} boards: {} states, no distance known", size, size, table.len());
+        return;
+    }
+    println!("{}x{} boards: {} states, diameter {}", size, size, table.len(), hist.len() - 1);
+    for (d, count) in hist.iter().enumerate() {
+        println!("{:3}: {}", d, count);
+    }
+}
+
+fn usage() -> ! {
+    println!("Usage: 262_hard table build <table> [size]");
+    println!("       262_hard table stats <table>");
+    println!("       262_hard table lookup <table> <boards>");
+    ::std::process::exit(1);
+}
+
+pub fn run(args: &[String]) {
+    if args.len() < 2 {
+        usage();
+    }
+    let path = args[1].as_str();
+    match args[0].as_str() {
+        "build" => {
+            let size = args.get(2).map_or(3, |s| s.parse::<usize>().unwrap_or_else(|_| usage()));
+            if size > 3 {
+                println!("Tables are only supported up to 3x3");
+                ::std::process::exit(1);
+            }
+            let table = build(size);
+            save(path, &table).expect("Cannot write table");
+            print_stats(&table);
+        },
+        "stats" => print_stats(&load(path).expect("Table not readable")),
+        "lookup" if args.len() == 3 => {
+            let table = load(path).expect("Table not readable");
+            let boards = parse_input_file(args[2].as_str()).expect("File not readable");
+            for w in &boards {
+                println!("");
+                w.print();
+                if !w.is_square() || Some(w.width) != size_of(&table) {
+                    println!("Board size does not match the table");
+                    continue;
+                }
+                if !w.is_permutation() {
+                    println!("Tables only cover permutations of 1..{}", w.data.len());
+                    continue;
+                }
+                let path = optimal_path(&table, w);
+                let moves: Vec<String> = path.iter()
+                    .map(|&(c0, c1)| format!("({}, {}) and ({}, {})", c0.0, c0.1, c1.0, c1.1))
+                    .collect();
+                println!("Distance {}: {}", lookup(&table, w), moves.join(", "));
+            }
+        },
+        _ => usage(),
+    }
+}
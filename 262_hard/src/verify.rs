@@ -0,0 +1,98 @@
+use generate::distance;
+use lib::astar::Graph;
+use std::fs::File;
+use std::io::prelude::*;
+use std::rc::Rc;
+use world::{Coord, World, parse_input_file};
+
+fn parse_coord(s: &str) -> Option<Coord> {
+    let parts: Vec<&str> = s.split(',').map(|p| p.trim()).collect();
+    if parts.len() != 2 {
+        return None;
+    }
+    match (parts[0].parse::<usize>(), parts[1].parse::<usize>()) {
+        (Ok(y), Ok(x)) => Some(Coord(y, x)),
+        _ => None,
+    }
+}
+
+// Extract every "(y, x) and (y, x)" swap, ignoring any other text, so the
+// solver output can be fed back as it is.
+pub fn parse_moves(text: &str) -> Result<Vec<(Coord, Coord)>, String> {
+    let mut moves = Vec::new();
+
+    for (n, line) in text.lines().enumerate() {
+        let mut coords = Vec::new();
+        let mut rest = line;
+        while let Some(start) = rest.find('(') {
+            let end = match rest[start..].find(')') {
+                Some(end) => start + end,
+                None => return Err(format!("Line {}: unclosed parenthesis", n + 1)),
+            };
+            match parse_coord(&rest[(start + 1)..end]) {
+                Some(c) => coords.push(c),
+                None => return Err(format!("Line {}: invalid coordinate '{}'", n + 1, &rest[start..(end + 1)])),
+            }
+            rest = &rest[(end + 1)..];
+        }
+        if coords.len() % 2 != 0 {
+            return Err(format!("Line {}: coordinate without a pair", n + 1));
+        }
+        for pair in coords.chunks(2) {
+            moves.push((pair[0], pair[1]));
+        }
+    }
+    Ok(moves)
+}
+
+fn is_valid(world: &World, c1: Coord, c2: Coord) -> bool {
+    let dist = (c1.0 as i32 - c2.0 as i32).abs() + (c1.1 as i32 - c2.1 as i32).abs();
+    c1.0 < world.height && c1.1 < world.width && c2.0 < world.height && c2.1 < world.width && dist == 1
+}
+
+pub fn replay(world: &World, moves: &[(Coord, Coord)]) -> Result<World, String> {
+    let mut w = world.clone();
+    for (n, &(c1, c2)) in moves.iter().enumerate() {
+        if !is_valid(&w, c1, c2) {
+            return Err(format!("Move {}: ({}, {}) and ({}, {}) are not neighbors", n + 1, c1.0, c1.1, c2.0, c2.1));
+        }
+        w.swap(c1, c2);
+    }
+    Ok(w)
+}
+
+fn usage() -> ! {
+    println!("Usage: 262_hard verify <board> <moves>");
+    ::std::process::exit(1);
+}
+
+pub fn run(args: &[String]) {
+    if args.len() != 2 {
+        usage();
+    }
+    let boards = parse_input_file(args[0].as_str()).expect("File not readable");
+    let world = boards.first().expect("No board found");
+    let mut text = String::new();
+    File::open(args[1].as_str()).and_then(|mut f| f.read_to_string(&mut text)).expect("File not readable");
+
+    let moves = match parse_moves(text.as_str()) {
+        Ok(moves) => moves,
+        Err(e) => { println!("Invalid move list: {}", e); return }
+    };
+    world.print();
+    let end = match replay(world, &moves) {
+        Ok(end) => end,
+        Err(e) => { println!("Invalid move list: {}", e); return }
+    };
+    println!("\nAfter {} moves:", moves.len());
+    end.print();
+    if !end.is_goal(&Rc::new(end.data.clone())) {
+        println!("The board is not sorted");
+        return;
+    }
+    match distance(world) {
+        Some(d) if d == moves.len() => println!("The board is sorted, the solution is optimal"),
+        Some(d) => println!("The board is sorted, {} moves more than the optimum of {}", moves.len() - d, d),
+        None => println!("The board is sorted"),
+    }
+}
@@ -0,0 +1,244 @@
+use assign;
+use itertools::Itertools;
+use lib::astar::Graph;
+use std::io;
+use std::io::prelude::*;
+use std::io::BufReader;
+use std::fs::File;
+use std::rc::Rc;
+
+#[derive(Copy, Clone, Debug)]
+pub struct Coord(pub usize, pub usize);
+
+// How much a swap costs, as a function of the two values exchanged.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Cost {
+    Unit,
+    Sum,
+    Max,
+}
+
+impl Cost {
+    pub fn parse(s: &str) -> Option<Cost> {
+        match s {
+            "unit" => Some(Cost::Unit),
+            "sum" => Some(Cost::Sum),
+            "max" => Some(Cost::Max),
+            _ => None,
+        }
+    }
+
+    pub fn of(&self, a: i32, b: i32) -> i32 {
+        match *self {
+            Cost::Unit => 1,
+            Cost::Sum => a + b,
+            Cost::Max => ::std::cmp::max(a, b),
+        }
+    }
+}
+
+// Boards are usually square, but the swap puzzle itself works on any
+// rectangle.
+#[derive(Clone)]
+pub struct World {
+    pub data: Vec<i32>,
+    pub height: usize,
+    pub width: usize,
+    pub pairs: Vec<(Coord, Coord)>,
+    pub cost: Cost,
+}
+
+pub fn create_pairs(height: usize, width: usize) -> Vec<(Coord, Coord)> {
+    let p1 = (0..height as i32).cartesian_product((0..width as i32));
+    let p2 = (0..height as i32).cartesian_product((0..width as i32));
+    p1.cartesian_product(p2)
+        .filter(|&((y1, x1), (y2, x2))| { ((y1 - y2).abs() + (x1 - x2).abs() == 1) && y2 >= y1 && x2 >= x1 })
+        .map(|((y1, x1), (y2, x2))| { (Coord(y1 as usize, x1 as usize), Coord(y2 as usize, x2 as usize)) })
+        .collect()
+}
+
+impl World {
+    // The values row after row, `width` per row.
+    pub fn new(width: usize, values: &[i32]) -> World {
+        let mut data = Vec::new();
+        data.extend_from_slice(values);
+        let height = data.len() / width;
+        World {height: height, width: width, data: data, pairs: create_pairs(height, width), cost: Cost::Unit}
+    }
+
+    pub fn goal(size: usize) -> World {
+        let values: Vec<i32> = (1..(size * size + 1) as i32).collect();
+        World::new(size, &values)
+    }
+
+    pub fn sorted(&self) -> World {
+        let mut values = self.data.clone();
+        values.sort();
+        World { data: values, ..self.clone() }
+    }
+
+    pub fn is_square(&self) -> bool {
+        self.height == self.width
+    }
+
+    // Whether the board holds exactly the values 1..n, each once, where n
+    // is the number of cells.
+    pub fn is_permutation(&self) -> bool {
+        self.sorted().data.iter().enumerate().all(|(i, &v)| v == i as i32 + 1)
+    }
+
+    pub fn get(&self, coord: Coord) -> i32 {
+        self.data[coord.0 * self.width + coord.1]
+    }
+
+    pub fn swap(&mut self, c1: Coord, c2: Coord) {
+        self.data.swap(c1.0 * self.width + c1.1, c2.0 * self.width + c2.1);
+    }
+
+    pub fn swap_cost(&self, node: &WNode, &(c1, c2): &(Coord, Coord)) -> i32 {
+        self.cost.of(node[c1.0 * self.width + c1.1], node[c2.0 * self.width + c2.1])
+    }
+
+    pub fn path_cost(&self, path: &[((Coord, Coord), WNode)]) -> i32 {
+        path.iter().fold(0, |acc, &(mov, ref node)| acc + self.swap_cost(node, &mov))
+    }
+
+    pub fn write<W: Write>(&self, out: &mut W) -> io::Result<()> {
+        for y in 0..self.height {
+            for x in 0..self.width {
+                try!(write!(out, "{:3}", self.get(Coord(y, x))));
+            }
+            try!(writeln!(out, ""));
+        }
+        Ok(())
+    }
+
+    pub fn print(&self) {
+        self.write(&mut io::stdout()).unwrap();
+    }
+}
+
+pub type WNode = Rc<Vec<i32>>;
+
+impl Graph for World {
+    type Node = WNode;
+    type Move = (Coord, Coord);
+
+    fn is_goal(&self, node: &Self::Node) -> bool {
+        node.windows(2).all(|w| w[0] <= w[1])
+    }
+
+    fn neighbors(&self, node: &Self::Node) -> Vec<(Self::Move, Self::Node)> {
+        let mut res = Vec::new();
+        for &(c1, c2) in &self.pairs {
+            let mut new = node.as_ref().clone();
+            new[c1.0 * self.width + c1.1] = node[c2.0 * self.width + c2.1];
+            new[c2.0 * self.width + c2.1] = node[c1.0 * self.width + c1.1];
+            res.push(((c1, c2), Rc::new(new)));
+        }
+        res
+    }
+}
+
+fn manhattan(width: usize, a: usize, b: usize) -> i32 {
+    ((a / width) as i32 - (b / width) as i32).abs() + ((a % width) as i32 - (b % width) as i32).abs()
+}
+
+// For every distinct value, the total Manhattan distance its cells have to
+// travel to reach the cells holding that value in the sorted board. Equal
+// values can go to any of their target cells, so they are assigned at
+// minimum cost.
+pub fn distances(w: &World, n: &[i32]) -> Vec<(i32, i32)> {
+    let mut cells: Vec<usize> = (0..n.len()).collect();
+    cells.sort_by_key(|&i| n[i]);
+    let mut res = Vec::new();
+    let mut start = 0;
+
+    // After sorting, the k-th cell has to end up in position k.
+    while start < cells.len() {
+        let v = n[cells[start]];
+        let end = start + cells[start..].iter().take_while(|&&c| n[c] == v).count();
+        let d = if end - start == 1 {
+            manhattan(w.width, cells[start], start)
+        } else {
+            let matrix: Vec<Vec<i32>> = cells[start..end].iter()
+                .map(|&c| (start..end).map(|t| manhattan(w.width, c, t)).collect())
+                .collect();
+            assign::min_cost(&matrix)
+        };
+        res.push((v, d));
+        start = end;
+    }
+    res
+}
+
+pub fn h1(w: &World, n: &WNode) -> i32 {
+    distances(w, n).iter().fold(0, |acc, &(_, d)| acc + d) / 2
+}
+
+// Every value v has to travel at least its distance d, and each step of it
+// is part of a swap costing at least v (sum) or v / 2 (max).
+pub fn h_cost(w: &World, n: &WNode) -> i32 {
+    let tot = distances(w, n).iter().fold(0, |acc, &(v, d)| acc + match w.cost {
+        Cost::Unit => d,
+        Cost::Sum | Cost::Max => d * v,
+    });
+    match w.cost {
+        Cost::Sum => tot,
+        Cost::Unit | Cost::Max => tot / 2,
+    }
+}
+
+fn invalid(msg: String) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, msg)
+}
+
+fn push_board(boards: &mut Vec<World>, rows: &mut Vec<Vec<i32>>) -> Result<(), io::Error> {
+    if rows.is_empty() {
+        return Ok(());
+    }
+    let width = rows[0].len();
+    if let Some(row) = rows.iter().find(|r| r.len() != width) {
+        return Err(invalid(format!("Board {} has a row of {} values, expected {}", boards.len() + 1, row.len(), width)));
+    }
+    let values: Vec<i32> = rows.iter().flat_map(|r| r.iter().cloned()).collect();
+    boards.push(World::new(width, &values));
+    rows.clear();
+    Ok(())
+}
+
+// Boards are written as rows of whitespace separated numbers, all as long
+// as the first one, and are separated by empty lines. Lines starting with '#' are comments.
+pub fn parse_boards<R: BufRead>(reader: R) -> Result<Vec<World>, io::Error> {
+    let mut boards = Vec::new();
+    let mut rows = Vec::new();
+
+    for line in reader.lines() {
+        let line = try!(line);
+        let line = line.trim();
+        if line.starts_with('#') {
+            continue;
+        }
+        if line.is_empty() {
+            try!(push_board(&mut boards, &mut rows));
+            continue;
+        }
+        let mut row = Vec::new();
+        for token in line.split_whitespace() {
+            // Swap costs are made of the values, so they have to be positive.
+            match token.parse::<i32>() {
+                Ok(v) if v > 0 => row.push(v),
+                Ok(v) => return Err(invalid(format!("Value {} is not positive", v))),
+                Err(_) => return Err(invalid(format!("Invalid value '{}'", token))),
+            }
+        }
+        rows.push(row);
+    }
+    try!(push_board(&mut boards, &mut rows));
+    Ok(boards)
+}
+
+pub fn parse_input_file(path: &str) -> Result<Vec<World>, io::Error> {
+    let f = try!(File::open(path));
+    parse_boards(BufReader::new(f))
+}
@@ -42,28 +42,59 @@ fn build_path<G>(come_from: &HashMap<G::Node, (G::Move, G::Node)>, end: G::Node)
     ret
 }
 
+// Not every crate sharing this module calls it.
+#[allow(dead_code)]
 pub fn a_star<G, H>(graph: &G, start: G::Node, h: H) -> (usize, Option<Vec<(G::Move, G::Node)>>)
     where G: Graph,
           H: Fn(&G, &G::Node) -> i32
+{
+    a_star_weighted(graph, start, h, |_, _, _| 1)
+}
+
+// Same as a_star, but each move costs c(graph, node, move) instead of 1.
+pub fn a_star_weighted<G, H, C>(graph: &G, start: G::Node, h: H, c: C) -> (usize, Option<Vec<(G::Move, G::Node)>>)
+    where G: Graph,
+          H: Fn(&G, &G::Node) -> i32,
+          C: Fn(&G, &G::Node, &G::Move) -> i32
+{
+    match a_star_until(graph, start, h, c, || false) {
+        (counter, Ok(path)) => (counter, path),
+        (_, Err(_)) => unreachable!(),
+    }
+}
+
+// Same as a_star_weighted, giving up as soon as stop() returns true. The
+// error is then the highest f value expanded: no path costs less.
+pub fn a_star_until<G, H, C, S>(graph: &G, start: G::Node, h: H, c: C, stop: S)
+                                -> (usize, Result<Option<Vec<(G::Move, G::Node)>>, i32>)
+    where G: Graph,
+          H: Fn(&G, &G::Node) -> i32,
+          C: Fn(&G, &G::Node, &G::Move) -> i32,
+          S: Fn() -> bool
 {
     let mut frontier: BinaryHeap<FNode<G::Node>> = BinaryHeap::new();
     let mut come_from: HashMap<G::Node, (G::Move, G::Node)> = HashMap::new();
     let mut cost: HashMap<G::Node, i32> = HashMap::new();
     let mut counter = 0;
+    let mut depth = 0;
     let max = std::i32::MAX;
 
     frontier.push(FNode(0, start.clone()));
     cost.insert(start.clone(), 0);
 
-    while let Some(FNode(_, curr)) = frontier.pop() {
+    while let Some(FNode(f, curr)) = frontier.pop() {
         counter += 1;
         if graph.is_goal(&curr) {
             let path = build_path::<G>(&come_from, curr);
-            return (counter, Some(path));
+            return (counter, Ok(Some(path)));
+        }
+        depth = std::cmp::max(depth, f);
+        if stop() {
+            return (counter, Err(depth));
         }
 
         for (mov, next) in graph.neighbors(&curr) {
-            let new_cost = cost[&curr] + 1;
+            let new_cost = cost[&curr] + c(graph, &curr, &mov);
             let old_cost = *cost.get(&next).unwrap_or(&max);
             if new_cost < old_cost {
                 cost.insert(next.clone(), new_cost);
@@ -72,5 +103,5 @@ pub fn a_star<G, H>(graph: &G, start: G::Node, h: H) -> (usize, Option<Vec<(G::M
             }
         }
     }
-    (counter, None)
+    (counter, Ok(None))
 }